@@ -0,0 +1,773 @@
+//! MINI-RPC Code Generation.
+//!
+//! Generates Rust code from an [OpenRPC](https://spec.open-rpc.org) document:
+//!
+//! - a struct for each object schema with properties, found in the components or in the
+//!   params and results of the methods. Other schemas map to plain Rust types, or to
+//!   `minirpc::Value` when they cannot;
+//! - `{Title}Client`, wrapping a `minirpc::Client`, whose methods build the `Call` of
+//!   the methods with a result (with a reply decoding it) and the `Notification` of the
+//!   others. Params are given by name when the `paramStructure` of the method is
+//!   `by-name`, by position otherwise;
+//! - the `{Title}` trait to implement server-side, and `{Title}Server<S>`, a
+//!   `minirpc::Service` dispatching each method to it.
+//!
+//! The generated code depends on `minirpc`, `serde` and `serde_derive`, and is meant to
+//! be generated by a build script:
+//!
+//! ```no_run
+//! // build.rs
+//! let output = format!("{}/calculator.rs", std::env::var("OUT_DIR").unwrap());
+//! println!("cargo:rerun-if-changed=calculator.json");
+//! minirpc_codegen::generate_file("calculator.json", output).unwrap();
+//! ```
+//!
+//! then included with `include!(concat!(env!("OUT_DIR"), "/calculator.rs"));`.
+//!
+//! The locals of the generated code are prefixed with `__`, which identifiers derived
+//! from the document never are, so that params can be given any name. Names which map
+//! to the same identifier (e.g. `userId` and `user_id`) are rejected.
+
+#[macro_use]
+extern crate serde_derive;
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error as StdError;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Rust type of the values without a more precise type.
+const VALUE: &str = "::minirpc::Value";
+
+/// Rust type of the objects without properties.
+const MAP: &str = "::minirpc::Map<String, ::minirpc::Value>";
+
+/// Rust type of the results.
+const RESULT: &str = "::std::result::Result";
+
+/// Prefix of the references to component schemas.
+const COMPONENTS: &str = "#/components/schemas/";
+
+/// Rust keywords, which cannot be used as identifiers as is.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
+    "try", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+/// Code generation error.
+#[derive(Debug)]
+pub enum Error {
+    /// The document could not be read, or the code written.
+    Io(io::Error),
+
+    /// The document is not a JSON OpenRPC document.
+    Parse(serde_json::Error),
+
+    /// The document cannot be turned into Rust code.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "IO error: {}", err),
+            Error::Parse(err) => write!(f, "Parse error: {}", err),
+            Error::Invalid(reason) => write!(f, "Invalid document: {}", reason),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+/// Returns the Rust code of given OpenRPC `document`.
+pub fn generate(document: &str) -> Result<String, Error> {
+    let document: Document = serde_json::from_str(document)?;
+    Generator::default().generate(&document)
+}
+
+/// Writes to `output` the Rust code of the OpenRPC document read from `input`.
+pub fn generate_file<P, Q>(input: P, output: Q) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let code = generate(&fs::read_to_string(input)?)?;
+    fs::write(output, code)?;
+    Ok(())
+}
+
+/// OpenRPC document.
+#[derive(Deserialize)]
+struct Document {
+    info: Info,
+    methods: Vec<MethodObject>,
+    #[serde(default)]
+    components: Components,
+}
+
+/// OpenRPC info object.
+#[derive(Deserialize)]
+struct Info {
+    title: String,
+}
+
+/// OpenRPC components object.
+#[derive(Default, Deserialize)]
+struct Components {
+    #[serde(default)]
+    schemas: BTreeMap<String, Value>,
+}
+
+/// OpenRPC method object.
+#[derive(Deserialize)]
+struct MethodObject {
+    name: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    params: Vec<ContentDescriptor>,
+    #[serde(default, rename = "paramStructure")]
+    param_structure: Option<String>,
+    #[serde(default)]
+    result: Option<ContentDescriptor>,
+}
+
+/// OpenRPC content descriptor object.
+#[derive(Deserialize)]
+struct ContentDescriptor {
+    name: String,
+    #[serde(default)]
+    schema: Value,
+    #[serde(default)]
+    required: bool,
+}
+
+/// Method of the generated client and server.
+struct Method<'a> {
+    object: &'a MethodObject,
+    ident: String,
+    params: Vec<Param<'a>>,
+    result: Option<String>,
+}
+
+/// Param of a generated method.
+struct Param<'a> {
+    name: &'a str,
+    ident: String,
+    ty: String,
+    required: bool,
+}
+
+/// Generator of the code of a document.
+#[derive(Default)]
+struct Generator {
+    /// Code of the generated types.
+    types: String,
+
+    /// Names of the generated types.
+    names: BTreeSet<String>,
+
+    /// Names of the component schemas.
+    components: BTreeSet<String>,
+}
+
+impl Generator {
+    fn generate(mut self, document: &Document) -> Result<String, Error> {
+        self.components = document.components.schemas.keys().cloned().collect();
+        for (name, schema) in &document.components.schemas {
+            let ident = pascal(named(name)?);
+            let ty = self.rust_type(schema, &ident)?;
+            if ty != ident {
+                self.names.insert(ident.clone());
+                writeln!(self.types, "pub type {} = {};\n", ident, ty).unwrap();
+            }
+        }
+
+        let mut idents = BTreeSet::new();
+        let mut methods = Vec::new();
+        for object in &document.methods {
+            let method = self.method(object)?;
+            if !idents.insert(method.ident.clone()) {
+                return Err(Error::Invalid(format!(
+                    "duplicate method `{}`",
+                    method.ident
+                )));
+            }
+            methods.push(method);
+        }
+
+        let title = pascal(named(&document.info.title)?);
+        let mut code = String::new();
+        code.push_str("// Generated by minirpc-codegen. Do not edit.\n\n");
+        code.push_str(&self.types);
+        client(&mut code, &title, &document.info.title, &methods);
+        server(&mut code, &title, &document.info.title, &methods);
+        Ok(code)
+    }
+
+    fn method<'a>(&mut self, object: &'a MethodObject) -> Result<Method<'a>, Error> {
+        let prefix = pascal(named(&object.name)?);
+        let mut params: Vec<Param> = Vec::new();
+        for param in &object.params {
+            let ident = ident(&snake(named(&param.name)?));
+            if params.iter().any(|other| other.ident == ident) {
+                return Err(Error::Invalid(format!(
+                    "duplicate param `{}` of method `{}`",
+                    ident, object.name
+                )));
+            }
+            let ty =
+                self.rust_type(&param.schema, &format!("{}{}", prefix, pascal(&param.name)))?;
+            params.push(Param {
+                name: &param.name,
+                ident,
+                ty: if param.required { ty } else { optional(ty) },
+                required: param.required,
+            });
+        }
+        let result = match &object.result {
+            Some(result) => Some(self.rust_type(&result.schema, &format!("{}Result", prefix))?),
+            None => None,
+        };
+
+        // Not to clash with the constructor of the client.
+        let ident = match ident(&snake(&object.name)) {
+            ident if ident == "new" => "new_".to_owned(),
+            ident => ident,
+        };
+        Ok(Method {
+            object,
+            ident,
+            params,
+            result,
+        })
+    }
+
+    /// Returns the Rust type of `schema`, generating a struct named `name` for it if
+    /// needed.
+    fn rust_type(&mut self, schema: &Value, name: &str) -> Result<String, Error> {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            _ => return Ok(VALUE.to_owned()),
+        };
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return match reference.strip_prefix(COMPONENTS) {
+                Some(component) if self.components.contains(component) => Ok(pascal(component)),
+                _ => Err(Error::Invalid(format!("unknown reference `{}`", reference))),
+            };
+        }
+
+        let types: Vec<_> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null");
+        let types: Vec<_> = types.into_iter().filter(|&ty| ty != "null").collect();
+
+        let ty = match types.as_slice() {
+            [] if nullable => return Ok("()".to_owned()),
+            ["object"] | [] if schema.contains_key("properties") => self.structure(schema, name)?,
+            ["object"] => MAP.to_owned(),
+            ["array"] => {
+                let items = schema.get("items").unwrap_or(&Value::Bool(true));
+                format!("Vec<{}>", self.rust_type(items, &format!("{}Item", name))?)
+            }
+            ["string"] => "String".to_owned(),
+            ["integer"] => "i64".to_owned(),
+            ["number"] => "f64".to_owned(),
+            ["boolean"] => "bool".to_owned(),
+            _ => return Ok(VALUE.to_owned()),
+        };
+        Ok(if nullable { optional(ty) } else { ty })
+    }
+
+    /// Generates the struct `name` of object `schema`.
+    fn structure(&mut self, schema: &Map<String, Value>, name: &str) -> Result<String, Error> {
+        if !self.names.insert(name.to_owned()) {
+            return Err(Error::Invalid(format!("duplicate type `{}`", name)));
+        }
+        let required: Vec<_> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut fields = String::new();
+        let mut idents = BTreeSet::new();
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (property, schema) in properties.into_iter().flatten() {
+            let field = ident(&snake(named(property)?));
+            if !idents.insert(field.clone()) {
+                return Err(Error::Invalid(format!(
+                    "duplicate field `{}` of type `{}`",
+                    field, name
+                )));
+            }
+            let ty = self.rust_type(schema, &format!("{}{}", name, pascal(property)))?;
+
+            let mut attributes = Vec::new();
+            // Raw identifiers are serialized without their prefix.
+            if field.strip_prefix("r#").unwrap_or(&field) != property {
+                attributes.push(format!("rename = {:?}", property));
+            }
+            let ty = if required.contains(&property.as_str()) {
+                ty
+            } else {
+                attributes.push("default, skip_serializing_if = \"Option::is_none\"".to_owned());
+                optional(ty)
+            };
+
+            doc(&mut fields, "    ", schema.get("description"));
+            if !attributes.is_empty() {
+                writeln!(fields, "    #[serde({})]", attributes.join(", ")).unwrap();
+            }
+            writeln!(fields, "    pub {}: {},", field, ty).unwrap();
+        }
+
+        doc(&mut self.types, "", schema.get("description"));
+        writeln!(
+            self.types,
+            "#[derive(Clone, Debug, PartialEq, ::serde_derive::Deserialize, ::serde_derive::Serialize)]"
+        )
+        .unwrap();
+        writeln!(self.types, "pub struct {} {{\n{}}}\n", name, fields).unwrap();
+        Ok(name.to_owned())
+    }
+}
+
+/// Writes the client of `methods`.
+fn client(code: &mut String, title: &str, name: &str, methods: &[Method]) {
+    writeln!(code, "/// Client of the `{}` methods.", name).unwrap();
+    writeln!(code, "#[derive(Clone)]").unwrap();
+    writeln!(code, "pub struct {}Client {{", title).unwrap();
+    writeln!(code, "    client: ::minirpc::Client,").unwrap();
+    writeln!(code, "}}\n").unwrap();
+    writeln!(code, "impl {}Client {{", title).unwrap();
+    writeln!(
+        code,
+        "    /// Creates a new client making its calls with `client`."
+    )
+    .unwrap();
+    writeln!(code, "    pub fn new(client: ::minirpc::Client) -> Self {{").unwrap();
+    writeln!(code, "        Self {{ client }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+
+    for method in methods {
+        let object = method.object;
+        code.push('\n');
+        match &object.summary {
+            Some(summary) => doc(code, "    ", Some(&Value::from(summary.as_str()))),
+            None if method.result.is_some() => {
+                writeln!(code, "    /// Builds the `Call` of `{}`.", object.name).unwrap()
+            }
+            None => writeln!(
+                code,
+                "    /// Builds the `Notification` of `{}`.",
+                object.name
+            )
+            .unwrap(),
+        }
+        writeln!(code, "    pub fn {}(", method.ident).unwrap();
+        writeln!(code, "        &self,").unwrap();
+        for param in &method.params {
+            writeln!(code, "        {}: {},", param.ident, param.ty).unwrap();
+        }
+        match &method.result {
+            Some(ty) => writeln!(
+                code,
+                "    ) -> {}<(::minirpc::Call, ::minirpc::client::Typed<{}>), ::minirpc::Error> {{",
+                RESULT, ty
+            ),
+            None => writeln!(
+                code,
+                "    ) -> {}<::minirpc::Notification, ::minirpc::Error> {{",
+                RESULT
+            ),
+        }
+        .unwrap();
+
+        if object.param_structure.as_deref() == Some("by-name") {
+            params_by_name(code, &method.params);
+        } else {
+            params_by_position(code, &method.params);
+        }
+
+        if method.result.is_some() {
+            writeln!(
+                code,
+                "        let (__call, __reply) = self.client.call({:?}, __params)?;",
+                object.name
+            )
+            .unwrap();
+            writeln!(code, "        {}::Ok((__call, __reply.typed()))", RESULT).unwrap();
+        } else {
+            writeln!(
+                code,
+                "        self.client.notify({:?}, __params)",
+                object.name
+            )
+            .unwrap();
+        }
+        writeln!(code, "    }}").unwrap();
+    }
+    writeln!(code, "}}\n").unwrap();
+}
+
+/// Writes the `params` of a client method, given by position.
+fn params_by_position(code: &mut String, params: &[Param]) {
+    // Trailing optional params are omitted, instead of being given as `null`.
+    let required = params
+        .iter()
+        .rposition(|param| param.required)
+        .map_or(0, |index| index + 1);
+    let trim = required < params.len();
+
+    let binding = if trim {
+        "let mut __values"
+    } else {
+        "let __values"
+    };
+    if params.is_empty() {
+        writeln!(code, "        {}: Vec<::minirpc::Value> = vec![];", binding).unwrap();
+    } else {
+        writeln!(code, "        {}: Vec<::minirpc::Value> = vec![", binding).unwrap();
+        for param in params {
+            writeln!(
+                code,
+                "            ::minirpc::service::to_value(&{})?,",
+                param.ident
+            )
+            .unwrap();
+        }
+        writeln!(code, "        ];").unwrap();
+    }
+    if trim {
+        writeln!(
+            code,
+            "        while __values.len() > {} && __values.last() == Some(&::minirpc::Value::Null) {{",
+            required
+        )
+        .unwrap();
+        writeln!(code, "            __values.pop();").unwrap();
+        writeln!(code, "        }}").unwrap();
+    }
+    writeln!(
+        code,
+        "        let __params = ::minirpc::Params::Array(__values);"
+    )
+    .unwrap();
+}
+
+/// Writes the `params` of a client method, given by name.
+fn params_by_name(code: &mut String, params: &[Param]) {
+    let binding = if params.is_empty() {
+        "let __map"
+    } else {
+        "let mut __map"
+    };
+    writeln!(code, "        {} = ::minirpc::Map::new();", binding).unwrap();
+    for param in params {
+        if param.required {
+            writeln!(
+                code,
+                "        __map.insert({:?}.to_owned(), ::minirpc::service::to_value(&{})?);",
+                param.name, param.ident
+            )
+            .unwrap();
+        } else {
+            // Omitted optional params are left out, instead of being given as `null`.
+            writeln!(code, "        if let Some({0}) = &{0} {{", param.ident).unwrap();
+            writeln!(
+                code,
+                "            __map.insert({:?}.to_owned(), ::minirpc::service::to_value({})?);",
+                param.name, param.ident
+            )
+            .unwrap();
+            writeln!(code, "        }}").unwrap();
+        }
+    }
+    writeln!(
+        code,
+        "        let __params = ::minirpc::Params::Object(__map);"
+    )
+    .unwrap();
+}
+
+/// Writes the server trait and the dispatcher of `methods`.
+fn server(code: &mut String, title: &str, name: &str, methods: &[Method]) {
+    writeln!(code, "/// Server of the `{}` methods.", name).unwrap();
+    writeln!(code, "pub trait {} {{", title).unwrap();
+    for (index, method) in methods.iter().enumerate() {
+        if index > 0 {
+            code.push('\n');
+        }
+        match &method.object.summary {
+            Some(summary) => doc(code, "    ", Some(&Value::from(summary.as_str()))),
+            None => writeln!(code, "    /// Handles `{}`.", method.object.name).unwrap(),
+        }
+        writeln!(code, "    fn {}(", method.ident).unwrap();
+        writeln!(code, "        &self,").unwrap();
+        writeln!(code, "        __context: &::minirpc::Context,").unwrap();
+        for param in &method.params {
+            writeln!(code, "        {}: {},", param.ident, param.ty).unwrap();
+        }
+        match &method.result {
+            Some(ty) => writeln!(code, "    ) -> {}<{}, ::minirpc::Error>;", RESULT, ty),
+            None => writeln!(code, "    );"),
+        }
+        .unwrap();
+    }
+    writeln!(code, "}}\n").unwrap();
+
+    let names: Vec<_> = methods
+        .iter()
+        .map(|method| format!("{:?}", method.object.name))
+        .collect();
+    writeln!(code, "/// Dispatcher of the `{}` methods.", name).unwrap();
+    writeln!(code, "#[derive(Clone, Debug)]").unwrap();
+    writeln!(code, "pub struct {}Server<S>(pub S);\n", title).unwrap();
+    writeln!(
+        code,
+        "impl<S> ::minirpc::service::Service for {}Server<S>",
+        title
+    )
+    .unwrap();
+    writeln!(code, "where").unwrap();
+    writeln!(code, "    S: {} + Send + Sync,", title).unwrap();
+    writeln!(code, "{{").unwrap();
+    writeln!(code, "    fn methods(&self) -> &'static [&'static str] {{").unwrap();
+    writeln!(code, "        &[{}]", names.join(", ")).unwrap();
+    writeln!(code, "    }}\n").unwrap();
+    writeln!(code, "    fn call(").unwrap();
+    writeln!(code, "        &self,").unwrap();
+    writeln!(code, "        __context: &::minirpc::Context,").unwrap();
+    writeln!(code, "        __method: &str,").unwrap();
+    writeln!(code, "        __params: ::minirpc::Params,").unwrap();
+    writeln!(
+        code,
+        "    ) -> {}<::minirpc::Value, ::minirpc::Error> {{",
+        RESULT
+    )
+    .unwrap();
+    writeln!(code, "        let _ = __context;").unwrap();
+    writeln!(code, "        #[allow(unused_mut)]").unwrap();
+    writeln!(
+        code,
+        "        let mut __args = ::minirpc::service::Args::new(__params);"
+    )
+    .unwrap();
+    writeln!(code, "        match __method {{").unwrap();
+    for method in methods {
+        writeln!(code, "            {:?} => {{", method.object.name).unwrap();
+        for param in &method.params {
+            writeln!(
+                code,
+                "                let {}: {} = __args.next({:?})?;",
+                param.ident, param.ty, param.name
+            )
+            .unwrap();
+        }
+        writeln!(code, "                __args.finish()?;").unwrap();
+
+        let mut args = vec!["__context".to_owned()];
+        args.extend(method.params.iter().map(|param| param.ident.clone()));
+        let call = format!("self.0.{}({})", method.ident, args.join(", "));
+        if method.result.is_some() {
+            writeln!(code, "                let __result = {}?;", call).unwrap();
+            writeln!(
+                code,
+                "                ::minirpc::service::to_value(&__result)"
+            )
+            .unwrap();
+        } else {
+            writeln!(code, "                {};", call).unwrap();
+            writeln!(
+                code,
+                "                {}::Ok(::minirpc::Value::Null)",
+                RESULT
+            )
+            .unwrap();
+        }
+        writeln!(code, "            }}").unwrap();
+    }
+    writeln!(
+        code,
+        "            _ => {}::Err(::minirpc::Error::new_method_not_found()),",
+        RESULT
+    )
+    .unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+}
+
+/// Writes the doc comment of given `description`, if any.
+fn doc(code: &mut String, indent: &str, description: Option<&Value>) {
+    if let Some(description) = description.and_then(Value::as_str) {
+        for line in description.lines() {
+            writeln!(code, "{}/// {}", indent, line).unwrap();
+        }
+    }
+}
+
+/// Returns `ty` made optional.
+fn optional(ty: String) -> String {
+    if ty.starts_with("Option<") {
+        ty
+    } else {
+        format!("Option<{}>", ty)
+    }
+}
+
+/// Returns the words of `name`, split on punctuation and case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous = None;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else {
+            let boundary = c.is_uppercase() && previous.is_some_and(char::is_lowercase);
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Returns `name` in snake case.
+fn snake(name: &str) -> String {
+    let words: Vec<_> = words(name).iter().map(|word| word.to_lowercase()).collect();
+    words.join("_")
+}
+
+/// Returns `name` in Pascal case, as a valid identifier.
+fn pascal(name: &str) -> String {
+    let name: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().into_iter().flat_map(char::to_uppercase);
+            first
+                .chain(chars.flat_map(char::to_lowercase))
+                .collect::<String>()
+        })
+        .collect();
+    ident(&name)
+}
+
+/// Returns `name`, unless it has no word to make an identifier of.
+fn named(name: &str) -> Result<&str, Error> {
+    if words(name).is_empty() {
+        return Err(Error::Invalid(format!("invalid name `{}`", name)));
+    }
+    Ok(name)
+}
+
+/// Returns `name`, made of at least one word, as a valid identifier.
+fn ident(name: &str) -> String {
+    match name {
+        "crate" | "self" | "Self" | "super" => format!("{}_", name),
+        name if KEYWORDS.contains(&name) => format!("r#{}", name),
+        name if name.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", name),
+        name => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codegen_idents() {
+        assert_eq!(snake("user.get"), "user_get");
+        assert_eq!(snake("userId"), "user_id");
+        assert_eq!(snake("HTTPServer"), "httpserver");
+        assert_eq!(pascal("user.get"), "UserGet");
+        assert_eq!(pascal("calculator api"), "CalculatorApi");
+        assert_eq!(ident(&snake("type")), "r#type");
+        assert_eq!(ident(&snake("self")), "self_");
+        assert_eq!(pascal("2fa"), "_2fa");
+    }
+
+    #[test]
+    fn codegen_invalid() {
+        let input = r##"{"info":{"title":"t"},"methods":[
+            {"name":"a","params":[{"name":"x","schema":{"$ref":"#/components/schemas/X"}}]}
+        ]}"##;
+        let result = generate(input).unwrap_err().to_string();
+        assert_eq!(
+            result,
+            "Invalid document: unknown reference `#/components/schemas/X`"
+        );
+
+        let input = r#"{"info":{"title":"t"},"methods":[{"name":"a.b"},{"name":"a_b"}]}"#;
+        let result = generate(input).unwrap_err().to_string();
+        assert_eq!(result, "Invalid document: duplicate method `a_b`");
+
+        let input = r#"{"info":{"title":"t"},"methods":[
+            {"name":"a","params":[{"name":"userId"},{"name":"user_id"}]}
+        ]}"#;
+        let result = generate(input).unwrap_err().to_string();
+        assert_eq!(
+            result,
+            "Invalid document: duplicate param `user_id` of method `a`"
+        );
+
+        let input = r#"{"info":{"title":"t"},"methods":[{"name":"a","params":[{"name":"-"}]}]}"#;
+        let result = generate(input).unwrap_err().to_string();
+        assert_eq!(result, "Invalid document: invalid name `-`");
+
+        let input = r#"{"info":{"title":"t"}}"#;
+        assert!(matches!(generate(input), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn codegen_renames() {
+        let input = r#"{"info":{"title":"t"},"methods":[{"name":"new","result":{"name":"r",
+            "schema":{"type":"object","properties":{"self":{},"2fa":{},"type":{},"id":{}}}}}
+        ]}"#;
+        let result = generate(input).unwrap();
+
+        // Fields are renamed whenever their identifier differs from their property.
+        assert!(result.contains("#[serde(rename = \"self\", default"));
+        assert!(result.contains("pub self_: Option<::minirpc::Value>,"));
+        assert!(result.contains("#[serde(rename = \"2fa\", default"));
+        assert!(result.contains("pub _2fa: Option<::minirpc::Value>,"));
+        assert!(result.contains(
+            "#[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub r#type:"
+        ));
+        assert!(!result.contains("rename = \"id\""));
+
+        // A method named `new` does not clash with the client constructor.
+        assert!(result.contains("pub fn new_("));
+        assert!(result.contains("self.0.new_(__context)"));
+    }
+}
@@ -0,0 +1,87 @@
+use minirpc::{Client, Context, Error, Request, RequestPayload, Response, Server};
+use std::sync::Mutex;
+
+mod generated {
+    include!("calculator/generated.rs");
+}
+
+use generated::*;
+
+#[derive(Debug, Default)]
+struct Memory {
+    entries: Mutex<Vec<(String, Entry)>>,
+}
+
+impl Calculator for Memory {
+    fn add(&self, _context: &Context, a: i64, b: Option<i64>) -> Result<i64, Error> {
+        Ok(a + b.unwrap_or(0))
+    }
+
+    fn memory_store(
+        &self,
+        _context: &Context,
+        slot: String,
+        entry: Entry,
+        _ttl_seconds: Option<i64>,
+    ) {
+        self.entries.lock().unwrap().push((slot, entry));
+    }
+
+    fn memory_list(&self, _context: &Context) -> Result<MemoryListResult, Error> {
+        let entries = self.entries.lock().unwrap();
+        Ok(MemoryListResult {
+            entries: entries.iter().map(|(_, entry)| entry.clone()).collect(),
+            total: entries.len() as i64,
+        })
+    }
+}
+
+#[test]
+fn codegen_generate() {
+    // The generated code checked in next to its document is up to date.
+    let input = include_str!("calculator/calculator.json");
+    let expected = include_str!("calculator/generated.rs");
+    assert_eq!(minirpc_codegen::generate(input).unwrap(), expected);
+}
+
+#[test]
+fn codegen_client() {
+    let mut server = Server::new();
+    server.serve(CalculatorServer(Memory::default())).unwrap();
+    let client = Client::new();
+    let calculator = CalculatorClient::new(client.clone());
+
+    let entry = Entry {
+        created_at: None,
+        r#type: Some("sum".to_owned()),
+        value: 3.0,
+    };
+    let notification = calculator
+        .memory_store("a".to_owned(), entry.clone(), None)
+        .unwrap();
+    let expected =
+        r#"{"method":"memory.store","params":{"entry":{"type":"sum","value":3.0},"slot":"a"}}"#;
+    assert_eq!(serde_json::to_string(&notification).unwrap(), expected);
+    server.handle(Request::Single(RequestPayload::Notification(notification)));
+
+    let (first, sum) = calculator.add(1, None).unwrap();
+    let (second, sum_b) = calculator.add(1, Some(2)).unwrap();
+    let (third, list) = calculator.memory_list().unwrap();
+    let expected = r#"{"id":1,"method":"add","params":[1]}"#;
+    assert_eq!(serde_json::to_string(&first).unwrap(), expected);
+
+    let request = Request::Batch(vec![
+        RequestPayload::Call(first),
+        RequestPayload::Call(second),
+        RequestPayload::Call(third),
+    ]);
+    let response: Option<Response> = server.handle(request);
+    client.receive(response.unwrap());
+    assert_eq!(sum.wait(), Ok(1));
+    assert_eq!(sum_b.wait(), Ok(3));
+    let expected = MemoryListResult {
+        entries: vec![entry],
+        total: 1,
+    };
+    assert_eq!(list.wait(), Ok(expected));
+}
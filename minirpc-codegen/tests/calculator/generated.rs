@@ -0,0 +1,149 @@
+// Generated by minirpc-codegen. Do not edit.
+
+/// Stored value.
+#[derive(Clone, Debug, PartialEq, ::serde_derive::Deserialize, ::serde_derive::Serialize)]
+pub struct Entry {
+    #[serde(rename = "createdAt", default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    pub value: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, ::serde_derive::Deserialize, ::serde_derive::Serialize)]
+pub struct MemoryListResult {
+    pub entries: Vec<Entry>,
+    /// Number of entries.
+    pub total: i64,
+}
+
+/// Client of the `calculator` methods.
+#[derive(Clone)]
+pub struct CalculatorClient {
+    client: ::minirpc::Client,
+}
+
+impl CalculatorClient {
+    /// Creates a new client making its calls with `client`.
+    pub fn new(client: ::minirpc::Client) -> Self {
+        Self { client }
+    }
+
+    /// Adds two numbers.
+    pub fn add(
+        &self,
+        a: i64,
+        b: Option<i64>,
+    ) -> ::std::result::Result<(::minirpc::Call, ::minirpc::client::Typed<i64>), ::minirpc::Error> {
+        let mut __values: Vec<::minirpc::Value> = vec![
+            ::minirpc::service::to_value(&a)?,
+            ::minirpc::service::to_value(&b)?,
+        ];
+        while __values.len() > 1 && __values.last() == Some(&::minirpc::Value::Null) {
+            __values.pop();
+        }
+        let __params = ::minirpc::Params::Array(__values);
+        let (__call, __reply) = self.client.call("add", __params)?;
+        ::std::result::Result::Ok((__call, __reply.typed()))
+    }
+
+    /// Builds the `Notification` of `memory.store`.
+    pub fn memory_store(
+        &self,
+        slot: String,
+        entry: Entry,
+        ttl_seconds: Option<i64>,
+    ) -> ::std::result::Result<::minirpc::Notification, ::minirpc::Error> {
+        let mut __map = ::minirpc::Map::new();
+        __map.insert("slot".to_owned(), ::minirpc::service::to_value(&slot)?);
+        __map.insert("entry".to_owned(), ::minirpc::service::to_value(&entry)?);
+        if let Some(ttl_seconds) = &ttl_seconds {
+            __map.insert("ttlSeconds".to_owned(), ::minirpc::service::to_value(ttl_seconds)?);
+        }
+        let __params = ::minirpc::Params::Object(__map);
+        self.client.notify("memory.store", __params)
+    }
+
+    /// Builds the `Call` of `memory.list`.
+    pub fn memory_list(
+        &self,
+    ) -> ::std::result::Result<(::minirpc::Call, ::minirpc::client::Typed<MemoryListResult>), ::minirpc::Error> {
+        let __values: Vec<::minirpc::Value> = vec![];
+        let __params = ::minirpc::Params::Array(__values);
+        let (__call, __reply) = self.client.call("memory.list", __params)?;
+        ::std::result::Result::Ok((__call, __reply.typed()))
+    }
+}
+
+/// Server of the `calculator` methods.
+pub trait Calculator {
+    /// Adds two numbers.
+    fn add(
+        &self,
+        __context: &::minirpc::Context,
+        a: i64,
+        b: Option<i64>,
+    ) -> ::std::result::Result<i64, ::minirpc::Error>;
+
+    /// Handles `memory.store`.
+    fn memory_store(
+        &self,
+        __context: &::minirpc::Context,
+        slot: String,
+        entry: Entry,
+        ttl_seconds: Option<i64>,
+    );
+
+    /// Handles `memory.list`.
+    fn memory_list(
+        &self,
+        __context: &::minirpc::Context,
+    ) -> ::std::result::Result<MemoryListResult, ::minirpc::Error>;
+}
+
+/// Dispatcher of the `calculator` methods.
+#[derive(Clone, Debug)]
+pub struct CalculatorServer<S>(pub S);
+
+impl<S> ::minirpc::service::Service for CalculatorServer<S>
+where
+    S: Calculator + Send + Sync,
+{
+    fn methods(&self) -> &'static [&'static str] {
+        &["add", "memory.store", "memory.list"]
+    }
+
+    fn call(
+        &self,
+        __context: &::minirpc::Context,
+        __method: &str,
+        __params: ::minirpc::Params,
+    ) -> ::std::result::Result<::minirpc::Value, ::minirpc::Error> {
+        let _ = __context;
+        #[allow(unused_mut)]
+        let mut __args = ::minirpc::service::Args::new(__params);
+        match __method {
+            "add" => {
+                let a: i64 = __args.next("a")?;
+                let b: Option<i64> = __args.next("b")?;
+                __args.finish()?;
+                let __result = self.0.add(__context, a, b)?;
+                ::minirpc::service::to_value(&__result)
+            }
+            "memory.store" => {
+                let slot: String = __args.next("slot")?;
+                let entry: Entry = __args.next("entry")?;
+                let ttl_seconds: Option<i64> = __args.next("ttlSeconds")?;
+                __args.finish()?;
+                self.0.memory_store(__context, slot, entry, ttl_seconds);
+                ::std::result::Result::Ok(::minirpc::Value::Null)
+            }
+            "memory.list" => {
+                __args.finish()?;
+                let __result = self.0.memory_list(__context)?;
+                ::minirpc::service::to_value(&__result)
+            }
+            _ => ::std::result::Result::Err(::minirpc::Error::new_method_not_found()),
+        }
+    }
+}
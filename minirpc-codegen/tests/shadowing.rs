@@ -0,0 +1,74 @@
+use minirpc::{Client, Context, Error, Request, RequestPayload, Response, Server};
+
+mod generated {
+    include!("shadowing/generated.rs");
+}
+
+use generated::*;
+
+struct Echo;
+
+impl Shadowing for Echo {
+    fn by_position(
+        &self,
+        _context: &Context,
+        context: String,
+        args: i64,
+        values: Option<i64>,
+        params: Option<i64>,
+    ) -> Result<String, Error> {
+        Ok(format!("{} {} {:?} {:?}", context, args, values, params))
+    }
+
+    fn by_name(
+        &self,
+        _context: &Context,
+        map: i64,
+        result: i64,
+        _method: Option<String>,
+        call: Option<i64>,
+        reply: Option<i64>,
+    ) -> Result<i64, Error> {
+        Ok(map + result + call.unwrap_or(0) + reply.unwrap_or(0))
+    }
+
+    fn notify(&self, _context: &Context, _params: String) {}
+}
+
+#[test]
+fn codegen_shadowing_generate() {
+    // The generated code checked in next to its document is up to date.
+    let input = include_str!("shadowing/shadowing.json");
+    let expected = include_str!("shadowing/generated.rs");
+    assert_eq!(minirpc_codegen::generate(input).unwrap(), expected);
+}
+
+#[test]
+fn codegen_shadowing() {
+    // Params named like the locals of the generated code do not shadow them.
+    let mut server = Server::new();
+    server.serve(ShadowingServer(Echo)).unwrap();
+    let client = Client::new();
+    let shadowing = ShadowingClient::new(client.clone());
+
+    let notification = shadowing.notify("x".to_owned()).unwrap();
+    let expected = r#"{"method":"notify","params":{"params":"x"}}"#;
+    assert_eq!(serde_json::to_string(&notification).unwrap(), expected);
+    server.handle(Request::Single(RequestPayload::Notification(notification)));
+
+    let (first, echo) = shadowing
+        .by_position("a".to_owned(), 1, Some(2), None)
+        .unwrap();
+    let (second, sum) = shadowing.by_name(1, 2, None, Some(3), None).unwrap();
+    let expected = r#"{"id":2,"method":"by_name","params":{"call":3,"map":1,"result":2}}"#;
+    assert_eq!(serde_json::to_string(&second).unwrap(), expected);
+
+    let request = Request::Batch(vec![
+        RequestPayload::Call(first),
+        RequestPayload::Call(second),
+    ]);
+    let response: Option<Response> = server.handle(request);
+    client.receive(response.unwrap());
+    assert_eq!(echo.wait(), Ok("a 1 Some(2) None".to_owned()));
+    assert_eq!(sum.wait(), Ok(6));
+}
@@ -0,0 +1,156 @@
+// Generated by minirpc-codegen. Do not edit.
+
+/// Client of the `shadowing` methods.
+#[derive(Clone)]
+pub struct ShadowingClient {
+    client: ::minirpc::Client,
+}
+
+impl ShadowingClient {
+    /// Creates a new client making its calls with `client`.
+    pub fn new(client: ::minirpc::Client) -> Self {
+        Self { client }
+    }
+
+    /// Builds the `Call` of `by_position`.
+    pub fn by_position(
+        &self,
+        context: String,
+        args: i64,
+        values: Option<i64>,
+        params: Option<i64>,
+    ) -> ::std::result::Result<(::minirpc::Call, ::minirpc::client::Typed<String>), ::minirpc::Error> {
+        let mut __values: Vec<::minirpc::Value> = vec![
+            ::minirpc::service::to_value(&context)?,
+            ::minirpc::service::to_value(&args)?,
+            ::minirpc::service::to_value(&values)?,
+            ::minirpc::service::to_value(&params)?,
+        ];
+        while __values.len() > 2 && __values.last() == Some(&::minirpc::Value::Null) {
+            __values.pop();
+        }
+        let __params = ::minirpc::Params::Array(__values);
+        let (__call, __reply) = self.client.call("by_position", __params)?;
+        ::std::result::Result::Ok((__call, __reply.typed()))
+    }
+
+    /// Builds the `Call` of `by_name`.
+    pub fn by_name(
+        &self,
+        map: i64,
+        result: i64,
+        method: Option<String>,
+        call: Option<i64>,
+        reply: Option<i64>,
+    ) -> ::std::result::Result<(::minirpc::Call, ::minirpc::client::Typed<i64>), ::minirpc::Error> {
+        let mut __map = ::minirpc::Map::new();
+        __map.insert("map".to_owned(), ::minirpc::service::to_value(&map)?);
+        __map.insert("result".to_owned(), ::minirpc::service::to_value(&result)?);
+        if let Some(method) = &method {
+            __map.insert("method".to_owned(), ::minirpc::service::to_value(method)?);
+        }
+        if let Some(call) = &call {
+            __map.insert("call".to_owned(), ::minirpc::service::to_value(call)?);
+        }
+        if let Some(reply) = &reply {
+            __map.insert("reply".to_owned(), ::minirpc::service::to_value(reply)?);
+        }
+        let __params = ::minirpc::Params::Object(__map);
+        let (__call, __reply) = self.client.call("by_name", __params)?;
+        ::std::result::Result::Ok((__call, __reply.typed()))
+    }
+
+    /// Builds the `Notification` of `notify`.
+    pub fn notify(
+        &self,
+        params: String,
+    ) -> ::std::result::Result<::minirpc::Notification, ::minirpc::Error> {
+        let mut __map = ::minirpc::Map::new();
+        __map.insert("params".to_owned(), ::minirpc::service::to_value(&params)?);
+        let __params = ::minirpc::Params::Object(__map);
+        self.client.notify("notify", __params)
+    }
+}
+
+/// Server of the `shadowing` methods.
+pub trait Shadowing {
+    /// Handles `by_position`.
+    fn by_position(
+        &self,
+        __context: &::minirpc::Context,
+        context: String,
+        args: i64,
+        values: Option<i64>,
+        params: Option<i64>,
+    ) -> ::std::result::Result<String, ::minirpc::Error>;
+
+    /// Handles `by_name`.
+    fn by_name(
+        &self,
+        __context: &::minirpc::Context,
+        map: i64,
+        result: i64,
+        method: Option<String>,
+        call: Option<i64>,
+        reply: Option<i64>,
+    ) -> ::std::result::Result<i64, ::minirpc::Error>;
+
+    /// Handles `notify`.
+    fn notify(
+        &self,
+        __context: &::minirpc::Context,
+        params: String,
+    );
+}
+
+/// Dispatcher of the `shadowing` methods.
+#[derive(Clone, Debug)]
+pub struct ShadowingServer<S>(pub S);
+
+impl<S> ::minirpc::service::Service for ShadowingServer<S>
+where
+    S: Shadowing + Send + Sync,
+{
+    fn methods(&self) -> &'static [&'static str] {
+        &["by_position", "by_name", "notify"]
+    }
+
+    fn call(
+        &self,
+        __context: &::minirpc::Context,
+        __method: &str,
+        __params: ::minirpc::Params,
+    ) -> ::std::result::Result<::minirpc::Value, ::minirpc::Error> {
+        let _ = __context;
+        #[allow(unused_mut)]
+        let mut __args = ::minirpc::service::Args::new(__params);
+        match __method {
+            "by_position" => {
+                let context: String = __args.next("context")?;
+                let args: i64 = __args.next("args")?;
+                let values: Option<i64> = __args.next("values")?;
+                let params: Option<i64> = __args.next("params")?;
+                __args.finish()?;
+                let __result = self.0.by_position(__context, context, args, values, params)?;
+                ::minirpc::service::to_value(&__result)
+            }
+            "by_name" => {
+                let map: i64 = __args.next("map")?;
+                let result: i64 = __args.next("result")?;
+                let method: Option<String> = __args.next("method")?;
+                let call: Option<i64> = __args.next("call")?;
+                let reply: Option<i64> = __args.next("reply")?;
+                __args.finish()?;
+                let __result = self.0.by_name(__context, map, result, method, call, reply)?;
+                ::minirpc::service::to_value(&__result)
+            }
+            "notify" => {
+                let params: String = __args.next("params")?;
+                __args.finish()?;
+                self.0.notify(__context, params);
+                ::std::result::Result::Ok(::minirpc::Value::Null)
+            }
+            _ => ::std::result::Result::Err(::minirpc::Error::new_method_not_found()),
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! MINI-RPC Derive Macros.
+//!
+//! The [`macro@service`] attribute turns a trait into a MINI-RPC service:
+//!
+//! ```ignore
+//! #[minirpc::service]
+//! pub trait Calculator {
+//!     fn add(&self, a: i64, b: i64) -> Result<i64, minirpc::Error>;
+//!
+//!     #[rpc(name = "calculator.reset")]
+//!     fn reset(&self);
+//! }
+//! ```
+//!
+//! generates, next to the trait:
+//!
+//! - `CalculatorServer<S>`, a `minirpc::Service` dispatching each call and notification
+//!   by method name to the trait method of `S`, decoding its params by position or by
+//!   name. Serve it with `server.serve(CalculatorServer(calculator))?`.
+//! - `CalculatorClient`, wrapping a `minirpc::Client`, whose methods build the `Call`
+//!   of the trait methods returning a `Result` (with a reply decoding the result) and
+//!   the `Notification` of the others.
+//!
+//! Methods take `&self` and owned arguments, except for a `&Context` argument which is
+//! given the context of the request instead of a param.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, FnArg, GenericArgument, Ident, ItemTrait, LitStr, Pat, PathArguments,
+    ReturnType, TraitItem, TraitItemFn, Type,
+};
+
+/// Generates the dispatcher and the typed client of a service trait.
+#[proc_macro_attribute]
+pub fn service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        let error = syn::Error::new(Span::call_site(), "`service` takes no argument");
+        return error.to_compile_error().into();
+    }
+
+    let item = parse_macro_input!(item as ItemTrait);
+    match expand(item) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Method of a service trait.
+struct Method {
+    ident: Ident,
+    name: String,
+    args: Vec<Arg>,
+    output: Output,
+}
+
+/// Argument of a service method.
+enum Arg {
+    /// Context of the request.
+    Context,
+
+    /// Param decoded from the request.
+    Param { ident: Ident, ty: Box<Type> },
+}
+
+/// Result of a service method.
+enum Output {
+    /// No result, invoked by notification.
+    Unit,
+
+    /// `Result` of given success type, invoked by call.
+    Result(Box<Type>),
+}
+
+fn expand(mut item: ItemTrait) -> syn::Result<TokenStream2> {
+    let methods = item
+        .items
+        .iter_mut()
+        .filter_map(|item| match item {
+            TraitItem::Fn(method) => Some(parse_method(method)),
+            _ => None,
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let vis = &item.vis;
+    let trait_ident = &item.ident;
+    let server = format_ident!("{}Server", trait_ident);
+    let client = format_ident!("{}Client", trait_ident);
+
+    let names = methods.iter().map(|method| &method.name);
+    let arms = methods.iter().map(dispatch);
+    let calls = methods.iter().map(call);
+
+    let server_doc = format!("Dispatcher of the `{}` methods.", trait_ident);
+    let client_doc = format!("Typed client of the `{}` methods.", trait_ident);
+
+    Ok(quote! {
+        #item
+
+        #[doc = #server_doc]
+        #[derive(Clone, Debug)]
+        #vis struct #server<S>(pub S);
+
+        impl<S> ::minirpc::service::Service for #server<S>
+        where
+            S: #trait_ident + Send + Sync,
+        {
+            fn methods(&self) -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+
+            fn call(
+                &self,
+                __minirpc_context: &::minirpc::Context,
+                __minirpc_method: &str,
+                __minirpc_params: ::minirpc::Params,
+            ) -> ::std::result::Result<::minirpc::Value, ::minirpc::Error> {
+                let _ = __minirpc_context;
+                #[allow(unused_mut)]
+                let mut __minirpc_args = ::minirpc::service::Args::new(__minirpc_params);
+                match __minirpc_method {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(::minirpc::Error::new_method_not_found()),
+                }
+            }
+        }
+
+        #[doc = #client_doc]
+        #[derive(Clone)]
+        #vis struct #client {
+            client: ::minirpc::Client,
+        }
+
+        impl #client {
+            /// Creates a new typed client making its calls with `client`.
+            pub fn new(client: ::minirpc::Client) -> Self {
+                Self { client }
+            }
+
+            #(#calls)*
+        }
+    })
+}
+
+fn parse_method(method: &mut TraitItemFn) -> syn::Result<Method> {
+    let mut name = method.sig.ident.to_string();
+    let mut error = None;
+    method.attrs.retain(|attr| {
+        if !attr.path().is_ident("rpc") {
+            return true;
+        }
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name`"))
+            }
+        });
+        if let Err(e) = result {
+            error = Some(e);
+        }
+        false
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let sig = &method.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "service methods cannot be generic",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                sig,
+                "service methods must take `&self`",
+            ))
+        }
+    }
+
+    let args = inputs
+        .map(|input| match input {
+            FnArg::Typed(arg) => parse_arg(&arg.pat, &arg.ty),
+            FnArg::Receiver(receiver) => {
+                Err(syn::Error::new_spanned(receiver, "unexpected `self`"))
+            }
+        })
+        .collect::<syn::Result<_>>()?;
+
+    let output = match &sig.output {
+        ReturnType::Default => Output::Unit,
+        ReturnType::Type(_, ty) => Output::Result(result_type(ty)?),
+    };
+
+    Ok(Method {
+        ident: sig.ident.clone(),
+        name,
+        args,
+        output,
+    })
+}
+
+fn parse_arg(pat: &Pat, ty: &Type) -> syn::Result<Arg> {
+    if let Type::Reference(reference) = ty {
+        return match &*reference.elem {
+            Type::Path(path)
+                if reference.mutability.is_none()
+                    && path.path.segments.last().unwrap().ident == "Context" =>
+            {
+                Ok(Arg::Context)
+            }
+            _ => Err(syn::Error::new_spanned(
+                ty,
+                "service arguments must be owned, except for `&Context`",
+            )),
+        };
+    }
+
+    match pat {
+        Pat::Ident(pat) => Ok(Arg::Param {
+            ident: pat.ident.clone(),
+            ty: Box::new(ty.clone()),
+        }),
+        _ => Err(syn::Error::new_spanned(
+            pat,
+            "service arguments must be named",
+        )),
+    }
+}
+
+/// Returns `T` of a `Result<T, E>` type.
+fn result_type(ty: &Type) -> syn::Result<Box<Type>> {
+    if let Type::Path(path) = ty {
+        let segment = path.path.segments.last().unwrap();
+        if segment.ident == "Result" {
+            if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                if let Some(GenericArgument::Type(ty)) = arguments.args.first() {
+                    return Ok(Box::new(ty.clone()));
+                }
+            }
+        }
+    }
+    Err(syn::Error::new_spanned(
+        ty,
+        "service methods must return nothing or a `Result<T, minirpc::Error>`",
+    ))
+}
+
+/// Returns the match arm dispatching `method`.
+fn dispatch(method: &Method) -> TokenStream2 {
+    let ident = &method.ident;
+    let name = &method.name;
+    let decode = method.args.iter().filter_map(|arg| match arg {
+        Arg::Context => None,
+        Arg::Param { ident, ty } => {
+            let key = ident.to_string();
+            Some(quote! { let #ident: #ty = __minirpc_args.next(#key)?; })
+        }
+    });
+    let args = method.args.iter().map(|arg| match arg {
+        Arg::Context => quote! { __minirpc_context },
+        Arg::Param { ident, .. } => quote! { #ident },
+    });
+    let invoke = match method.output {
+        Output::Unit => quote! {
+            self.0.#ident(#(#args),*);
+            ::std::result::Result::Ok(::minirpc::Value::Null)
+        },
+        Output::Result(_) => quote! {
+            let __minirpc_result = self.0.#ident(#(#args),*)?;
+            ::minirpc::service::to_value(&__minirpc_result)
+        },
+    };
+
+    quote! {
+        #name => {
+            #(#decode)*
+            __minirpc_args.finish()?;
+            #invoke
+        }
+    }
+}
+
+/// Returns the client method invoking `method`.
+fn call(method: &Method) -> TokenStream2 {
+    let ident = &method.ident;
+    let name = &method.name;
+    let params: Vec<_> = method
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            Arg::Context => None,
+            Arg::Param { ident, ty } => Some((ident, ty)),
+        })
+        .collect();
+    let inputs = params.iter().map(|(ident, ty)| quote! { #ident: #ty });
+    let values = params
+        .iter()
+        .map(|(ident, _)| quote! { ::minirpc::service::to_value(&#ident)? });
+    let build = quote! {
+        let __minirpc_params = ::minirpc::Params::Array(vec![#(#values),*]);
+    };
+
+    match &method.output {
+        Output::Unit => {
+            let doc = format!("Builds the `Notification` of `{}`.", name);
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(
+                    &self,
+                    #(#inputs),*
+                ) -> ::std::result::Result<::minirpc::Notification, ::minirpc::Error> {
+                    #build
+                    self.client.notify(#name, __minirpc_params)
+                }
+            }
+        }
+        Output::Result(ty) => {
+            let doc = format!("Builds the `Call` of `{}`.", name);
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(
+                    &self,
+                    #(#inputs),*
+                ) -> ::std::result::Result<
+                    (::minirpc::Call, ::minirpc::client::Typed<#ty>),
+                    ::minirpc::Error,
+                > {
+                    #build
+                    let (__minirpc_call, __minirpc_reply) =
+                        self.client.call(#name, __minirpc_params)?;
+                    ::std::result::Result::Ok((__minirpc_call, __minirpc_reply.typed()))
+                }
+            }
+        }
+    }
+}
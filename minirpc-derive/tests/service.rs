@@ -0,0 +1,191 @@
+use minirpc::context::Peer;
+use minirpc::{Client, Context, Error, Request, RequestPayload, Response, Server};
+use minirpc_derive::service;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[service]
+pub trait Calculator {
+    fn add(&self, a: i64, b: i64) -> Result<i64, Error>;
+
+    fn div(&self, a: i64, b: Option<i64>) -> Result<i64, Error>;
+
+    fn whoami(&self, context: &Context) -> Result<String, Error>;
+
+    #[rpc(name = "calculator.reset")]
+    fn reset(&self, value: u64);
+
+    fn total(&self) -> Result<u64, Error>;
+}
+
+#[service]
+pub trait Shadowing {
+    fn echo(
+        &self,
+        _context: &Context,
+        args: String,
+        context: String,
+        params: Option<String>,
+    ) -> Result<String, Error>;
+
+    fn forget(&self, result: String, call: String);
+}
+
+struct Echo;
+
+impl Shadowing for Echo {
+    fn echo(
+        &self,
+        _context: &Context,
+        args: String,
+        context: String,
+        params: Option<String>,
+    ) -> Result<String, Error> {
+        Ok(format!("{} {} {:?}", args, context, params))
+    }
+
+    fn forget(&self, _result: String, _call: String) {}
+}
+
+#[derive(Debug, Default)]
+struct Memory {
+    total: AtomicU64,
+}
+
+impl Calculator for Memory {
+    fn add(&self, a: i64, b: i64) -> Result<i64, Error> {
+        Ok(a + b)
+    }
+
+    fn div(&self, a: i64, b: Option<i64>) -> Result<i64, Error> {
+        match b.unwrap_or(1) {
+            0 => Err(Error::new_invalid_params()),
+            b => Ok(a / b),
+        }
+    }
+
+    fn whoami(&self, context: &Context) -> Result<String, Error> {
+        let peer = context
+            .get::<Peer>()
+            .ok_or_else(Error::new_internal_error)?;
+        Ok(peer.0.clone())
+    }
+
+    fn reset(&self, value: u64) {
+        self.total.store(value, Ordering::SeqCst);
+    }
+
+    fn total(&self) -> Result<u64, Error> {
+        Ok(self.total.load(Ordering::SeqCst))
+    }
+}
+
+fn server() -> Server {
+    let mut server = Server::new();
+    server.serve(CalculatorServer(Memory::default())).unwrap();
+    server
+}
+
+fn handle(server: &Server, input: &str) -> String {
+    let request: Request = serde_json::from_str(input).unwrap();
+    serde_json::to_string(&server.handle(request)).unwrap()
+}
+
+#[test]
+fn service_dispatch() {
+    let server = server();
+
+    let input = r#"{"id":1,"method":"add","params":[1,2]}"#;
+    let expected = r#"{"id":1,"result":3}"#;
+    assert_eq!(handle(&server, input), expected);
+
+    let input = r#"{"id":1,"method":"add","params":{"b":2,"a":1}}"#;
+    assert_eq!(handle(&server, input), expected);
+
+    // Optional arguments can be omitted.
+    let input = r#"{"id":1,"method":"div","params":[3]}"#;
+    assert_eq!(handle(&server, input), expected);
+
+    let expected = r#"{"error":{"code":-32602,"message":"Invalid params"},"id":1}"#;
+    for input in &[
+        r#"{"id":1,"method":"add","params":[1]}"#,
+        r#"{"id":1,"method":"add","params":[1,2,3]}"#,
+        r#"{"id":1,"method":"add","params":[1,"2"]}"#,
+        r#"{"id":1,"method":"div","params":[1,0]}"#,
+    ] {
+        assert_eq!(handle(&server, input), expected);
+    }
+
+    let input = r#"{"method":"calculator.reset","params":[5]}"#;
+    assert_eq!(handle(&server, input), "null");
+
+    let input = r#"{"id":1,"method":"total","params":[]}"#;
+    let expected = r#"{"id":1,"result":5}"#;
+    assert_eq!(handle(&server, input), expected);
+
+    let input = r#"{"id":1,"method":"reset","params":[0]}"#;
+    let expected = r#"{"error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+    assert_eq!(handle(&server, input), expected);
+}
+
+#[test]
+fn service_context() {
+    let server = server();
+    let mut context = Context::new();
+    context.insert(Peer("127.0.0.1:4000".to_owned()));
+
+    let input = r#"{"id":1,"method":"whoami","params":[]}"#;
+    let expected = r#"{"id":1,"result":"127.0.0.1:4000"}"#;
+    let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+    assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+}
+
+#[test]
+fn service_client() {
+    let server = server();
+    let client = Client::new();
+    let calculator = CalculatorClient::new(client.clone());
+
+    let notification = calculator.reset(2).unwrap();
+    let expected = r#"{"method":"calculator.reset","params":[2]}"#;
+    assert_eq!(serde_json::to_string(&notification).unwrap(), expected);
+    server.handle(Request::Single(RequestPayload::Notification(notification)));
+
+    let (first, sum) = calculator.add(1, 2).unwrap();
+    let (second, total) = calculator.total().unwrap();
+    let expected = r#"{"id":1,"method":"add","params":[1,2]}"#;
+    assert_eq!(serde_json::to_string(&first).unwrap(), expected);
+
+    let request = Request::Batch(vec![
+        RequestPayload::Call(first),
+        RequestPayload::Call(second),
+    ]);
+    let response: Option<Response> = server.handle(request);
+    client.receive(response.unwrap());
+    assert_eq!(sum.wait(), Ok(3));
+    assert_eq!(total.wait(), Ok(2));
+}
+
+#[test]
+fn service_shadowing() {
+    // Params named like the locals of the generated code do not shadow them.
+    let mut server = Server::new();
+    server.serve(ShadowingServer(Echo)).unwrap();
+    let client = Client::new();
+    let shadowing = ShadowingClient::new(client.clone());
+
+    let notification = shadowing.forget("a".to_owned(), "b".to_owned()).unwrap();
+    assert_eq!(
+        server.handle(Request::Single(RequestPayload::Notification(notification))),
+        None
+    );
+
+    let (call, echo) = shadowing
+        .echo("a".to_owned(), "b".to_owned(), None)
+        .unwrap();
+    let expected = r#"{"id":1,"method":"echo","params":["a","b",null]}"#;
+    assert_eq!(serde_json::to_string(&call).unwrap(), expected);
+
+    let response = server.handle(Request::Single(RequestPayload::Call(call)));
+    client.receive(response.unwrap());
+    assert_eq!(echo.wait(), Ok("a b None".to_owned()));
+}
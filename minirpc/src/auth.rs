@@ -0,0 +1,397 @@
+//! MINI-RPC Authentication.
+//!
+//! The transport inserts the [`Credentials`] presented by the peer (e.g. a bearer token
+//! from an HTTP header or a handshake message, or an HMAC signature) in the `Context`.
+//! The [`Authenticate`] interceptor validates them before dispatch, answering failures
+//! with `Error::new_unauthorized()`, and exposes the resulting [`Principal`] to handlers
+//! through the `Context`.
+//!
+//! Signatures cover the raw request, as received by the transport, and the time it was
+//! signed at, so that a signature neither authenticates another request nor outlives its
+//! validity window. The transport verifies them once per request with
+//! [`Authenticate::verify`], before the request is decoded and a batch split up.
+
+use crate::interceptor::Interceptor;
+use crate::{Call, Context, Error, Notification};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Credentials presented by the peer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Credentials {
+    /// Bearer token.
+    Bearer(String),
+
+    /// Signature of the raw request, made at `timestamp` (in seconds since the
+    /// Unix epoch).
+    Signature { timestamp: u64, signature: Vec<u8> },
+}
+
+/// Authenticated identity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Principal {
+    name: String,
+    permissions: BTreeSet<String>,
+}
+
+impl Principal {
+    /// Creates a new `Principal` named `name`, without any permission.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            permissions: BTreeSet::new(),
+        }
+    }
+
+    /// Grants given `permission` to the principal.
+    pub fn with_permission(mut self, permission: &str) -> Self {
+        self.permissions.insert(permission.to_owned());
+        self
+    }
+
+    /// Returns the principal name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the principal was granted `permission`.
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+}
+
+/// Validates credentials.
+pub trait Authenticator: Send + Sync {
+    /// Returns the principal authenticated by given `credentials`, presented along with
+    /// the raw `request` (empty when the credentials are checked per call or
+    /// notification).
+    fn authenticate(&self, credentials: &Credentials, request: &[u8]) -> Result<Principal, Error>;
+}
+
+impl<A> Authenticator for Arc<A>
+where
+    A: Authenticator + ?Sized,
+{
+    fn authenticate(&self, credentials: &Credentials, request: &[u8]) -> Result<Principal, Error> {
+        (**self).authenticate(credentials, request)
+    }
+}
+
+/// Authenticator of static bearer tokens.
+#[derive(Clone, Debug, Default)]
+pub struct Tokens {
+    tokens: HashMap<String, Principal>,
+}
+
+impl Tokens {
+    /// Creates a new `Tokens` authenticator without any token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts given `token` as authenticating `principal`.
+    pub fn insert(&mut self, token: &str, principal: Principal) {
+        self.tokens.insert(token.to_owned(), principal);
+    }
+}
+
+impl Authenticator for Tokens {
+    fn authenticate(&self, credentials: &Credentials, _request: &[u8]) -> Result<Principal, Error> {
+        let token = match credentials {
+            Credentials::Bearer(token) => token,
+            _ => return Err(Error::new_unauthorized()),
+        };
+
+        // Compare every token in full, not to leak which one, nor which prefix, matched.
+        self.tokens
+            .iter()
+            .fold(None, |found, (candidate, principal)| {
+                if equal(candidate.as_bytes(), token.as_bytes()) {
+                    Some(principal)
+                } else {
+                    found
+                }
+            })
+            .cloned()
+            .ok_or_else(Error::new_unauthorized)
+    }
+}
+
+/// Compares `secret` with `input` in a time depending only on the length of `secret`.
+fn equal(secret: &[u8], input: &[u8]) -> bool {
+    let diff = secret
+        .iter()
+        .enumerate()
+        .fold(secret.len() ^ input.len(), |diff, (i, byte)| {
+            diff | usize::from(byte ^ input.get(i).copied().unwrap_or(0))
+        });
+    diff == 0
+}
+
+/// Default validity window of signatures, in seconds.
+#[cfg(feature = "hmac")]
+pub const DEFAULT_WINDOW: u64 = 300;
+
+/// Authenticator of HMAC-SHA256 message signatures.
+///
+/// Signatures are accepted once, within the validity window around their timestamp.
+#[cfg(feature = "hmac")]
+pub struct HmacSha256 {
+    key: Vec<u8>,
+    principal: Principal,
+    window: u64,
+    seen: std::sync::Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+#[cfg(feature = "hmac")]
+impl HmacSha256 {
+    /// Creates a new `HmacSha256` authenticator of the signatures made with `key`,
+    /// which authenticate `principal`.
+    pub fn new(key: &[u8], principal: Principal) -> Self {
+        Self {
+            key: key.to_vec(),
+            principal,
+            window: DEFAULT_WINDOW,
+            seen: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the validity window of signatures, in seconds either side of their timestamp.
+    pub fn with_window(mut self, window: u64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Signs given `request` at `timestamp` (in seconds since the Unix epoch), returning
+    /// the credentials to present along with it.
+    ///
+    /// The request is serialized with `serde_json::to_vec`, and must be sent as such.
+    pub fn sign<T>(&self, timestamp: u64, request: &T) -> Result<Credentials, serde_json::Error>
+    where
+        T: serde::Serialize,
+    {
+        use hmac::Mac;
+
+        let request = serde_json::to_vec(request)?;
+        let signature = self
+            .mac(timestamp, &request)
+            .finalize()
+            .into_bytes()
+            .to_vec();
+        Ok(Credentials::Signature {
+            timestamp,
+            signature,
+        })
+    }
+
+    fn mac(&self, timestamp: u64, request: &[u8]) -> hmac::Hmac<sha2::Sha256> {
+        use hmac::Mac;
+
+        // HMAC accepts keys of any length.
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&self.key).unwrap();
+        mac.update(&timestamp.to_be_bytes());
+        mac.update(request);
+        mac
+    }
+}
+
+#[cfg(feature = "hmac")]
+impl Authenticator for HmacSha256 {
+    fn authenticate(&self, credentials: &Credentials, request: &[u8]) -> Result<Principal, Error> {
+        use hmac::Mac;
+
+        let (timestamp, signature) = match credentials {
+            Credentials::Signature {
+                timestamp,
+                signature,
+            } => (*timestamp, signature),
+            _ => return Err(Error::new_unauthorized()),
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        if now.abs_diff(timestamp) > self.window {
+            return Err(Error::new_unauthorized());
+        }
+
+        self.mac(timestamp, request)
+            .verify_slice(signature)
+            .map_err(|_| Error::new_unauthorized())?;
+
+        // Reject replays, remembering signatures only as long as they are valid.
+        let mut seen = self.seen.lock().unwrap();
+        let window = self.window;
+        seen.retain(|_, &mut timestamp| timestamp.saturating_add(window) >= now);
+        if seen.insert(signature.clone(), timestamp).is_some() {
+            return Err(Error::new_unauthorized());
+        }
+        Ok(self.principal.clone())
+    }
+}
+
+/// Interceptor rejecting unauthenticated calls and notifications.
+///
+/// Calls and notifications of a request verified by [`Authenticate::verify`] are
+/// accepted as is. Otherwise, the credentials are checked for each of them, which only
+/// bearer tokens pass.
+pub struct Authenticate<A> {
+    authenticator: A,
+}
+
+impl<A> Authenticate<A>
+where
+    A: Authenticator,
+{
+    /// Creates a new `Authenticate` interceptor validating credentials with `authenticator`.
+    pub fn new(authenticator: A) -> Self {
+        Self { authenticator }
+    }
+
+    /// Authenticates the credentials of `context` presented along with the raw `request`,
+    /// and inserts the resulting [`Principal`] in `context`.
+    ///
+    /// Transports call it once per request, before decoding it, then handle the request
+    /// with `context`.
+    pub fn verify(&self, context: &mut Context, request: &[u8]) -> Result<(), Error> {
+        let credentials = context
+            .get::<Credentials>()
+            .ok_or_else(Error::new_unauthorized)?;
+        let principal = self.authenticator.authenticate(credentials, request)?;
+        context.insert(principal);
+        Ok(())
+    }
+
+    fn authenticate(&self, context: &mut Context) -> Result<(), Error> {
+        if context.get::<Principal>().is_some() {
+            return Ok(());
+        }
+        self.verify(context, &[])
+    }
+}
+
+impl<A> Interceptor for Authenticate<A>
+where
+    A: Authenticator,
+{
+    fn before_call(&self, context: &mut Context, _call: &mut Call) -> Result<(), Error> {
+        self.authenticate(context)
+    }
+
+    fn before_notification(
+        &self,
+        context: &mut Context,
+        _notification: &mut Notification,
+    ) -> Result<(), Error> {
+        self.authenticate(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Params, Server};
+    use serde_json::Value;
+
+    fn tokens() -> Tokens {
+        let mut tokens = Tokens::new();
+        tokens.insert("secret", Principal::new("alice"));
+        tokens
+    }
+
+    #[test]
+    fn tokens_authenticate() {
+        let result = tokens().authenticate(&Credentials::Bearer("secret".to_owned()), &[]);
+        assert_eq!(result, Ok(Principal::new("alice")));
+
+        let result = tokens().authenticate(&Credentials::Bearer("secreT".to_owned()), &[]);
+        assert_eq!(result, Err(Error::new_unauthorized()));
+
+        let result = tokens().authenticate(&Credentials::Bearer("secret!".to_owned()), &[]);
+        assert_eq!(result, Err(Error::new_unauthorized()));
+    }
+
+    #[cfg(feature = "hmac")]
+    #[test]
+    fn hmac_authenticate() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let authenticator = Arc::new(HmacSha256::new(b"key", Principal::new("bob")));
+        let authenticate = Authenticate::new(authenticator.clone());
+        let mut server = Server::new();
+        server.intercept(Authenticate::new(authenticator));
+        server
+            .register("whoami", |context: &Context, _params: Params| {
+                let principal = context.get::<Principal>().unwrap();
+                Ok(Value::from(principal.name()))
+            })
+            .unwrap();
+        let handle = |credentials: Credentials, input: &str| {
+            let mut context = Context::new();
+            context.insert(credentials);
+            authenticate.verify(&mut context, input.as_bytes())?;
+            let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+            Ok(serde_json::to_string(&result).unwrap())
+        };
+
+        // A batch is signed and verified once, as a whole.
+        let input =
+            r#"[{"id":1,"method":"whoami","params":[]},{"id":2,"method":"whoami","params":[]}]"#;
+        let request: Value = serde_json::from_str(input).unwrap();
+        let signer = HmacSha256::new(b"key", Principal::new("bob"));
+        let credentials = signer.sign(now, &request).unwrap();
+        let expected = r#"[{"id":1,"result":"bob"},{"id":2,"result":"bob"}]"#;
+        assert_eq!(handle(credentials.clone(), input), Ok(expected.to_owned()));
+
+        // Replayed signature.
+        let unauthorized = Err(Error::new_unauthorized());
+        assert_eq!(handle(credentials, input), unauthorized);
+
+        // Signature of another request.
+        let other = r#"{"id":1,"method":"whoami","params":[]}"#;
+        let credentials = signer.sign(now, &request).unwrap();
+        assert_eq!(handle(credentials, other), unauthorized);
+
+        // Expired signature, and signature made with another key.
+        let credentials = signer.sign(now - 301, &request).unwrap();
+        assert_eq!(handle(credentials, input), unauthorized);
+        let signer = HmacSha256::new(b"other", Principal::new("bob"));
+        let credentials = signer.sign(now, &request).unwrap();
+        assert_eq!(handle(credentials, input), unauthorized);
+
+        // Signatures are not checked per call.
+        let mut context = Context::new();
+        context.insert(signer.sign(now, &request).unwrap());
+        let result = server.handle_with(&context, serde_json::from_str(other).unwrap());
+        let expected = r#"{"error":{"code":-32001,"message":"Unauthorized"},"id":1}"#;
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn authenticate_intercept() {
+        let mut server = Server::new();
+        server.intercept(Authenticate::new(tokens()));
+        server
+            .register("whoami", |context: &Context, _params: Params| {
+                let principal = context.get::<Principal>().unwrap();
+                Ok(Value::from(principal.name()))
+            })
+            .unwrap();
+        let input = r#"{"id":1,"method":"whoami","params":[]}"#;
+
+        let expected = r#"{"error":{"code":-32001,"message":"Unauthorized"},"id":1}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let mut context = Context::new();
+        context.insert(Credentials::Bearer("secret".to_owned()));
+
+        let expected = r#"{"id":1,"result":"alice"}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+}
@@ -3,9 +3,15 @@
 use crate::{Id, Method, Params};
 
 /// Represents a MINI-RPC Request which is a call.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+///
+/// The `deadline` envelope field is an extension, which strict servers reject.
+///
+/// Calls are built with [`Call::new`], so that envelope extensions can be added without
+/// breaking the code building calls.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
-pub struct Call {
+#[non_exhaustive]
+pub struct Call<P = Params> {
     /// Call request id.
     pub id: Id,
 
@@ -13,7 +19,29 @@ pub struct Call {
     pub method: Method,
 
     /// A Structured value that holds the parameter values to be used during the invocation of the method.
-    pub params: Params,
+    pub params: P,
+
+    /// Milliseconds the caller waits for the result, counted from when the call is sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<u64>,
+}
+
+impl<P> Call<P> {
+    /// Creates a new `Call` of `method` with `params`, identified by `id`.
+    pub fn new(id: Id, method: Method, params: P) -> Self {
+        Self {
+            id,
+            method,
+            params,
+            deadline: None,
+        }
+    }
+
+    /// Sets the milliseconds the caller waits for the result.
+    pub fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -28,6 +56,7 @@ mod tests {
             id: Id::Number(1),
             method: Method::String("test_method".to_owned()),
             params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+            deadline: None,
         };
 
         let result: Call = serde_json::from_str(input).unwrap();
@@ -40,10 +69,43 @@ mod tests {
             id: Id::Number(1),
             method: Method::String("test_method".to_owned()),
             params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+            deadline: None,
         };
         let expected = r#"{"id":1,"method":"test_method","params":[1,2,3]}"#;
 
         let result = serde_json::to_string(&input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn call_deadline() {
+        let input = r#"{"id":1,"method":"test_method","params":[],"deadline":1500}"#;
+        let expected = Call::new(
+            Id::Number(1),
+            Method::String("test_method".to_owned()),
+            Params::Array(vec![]),
+        )
+        .with_deadline(1500);
+
+        let result: Call = serde_json::from_str(input).unwrap();
+        assert_eq!(result, expected);
+
+        let result = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, input);
+    }
+
+    #[cfg(feature = "raw_value")]
+    #[test]
+    fn call_raw_value_passthrough() {
+        use serde_json::value::RawValue;
+
+        let input = r#"{"id":1,"method":"test_method","params":{"b":1, "a":[1,  2]}}"#;
+
+        let result: Call<Box<RawValue>> = serde_json::from_str(input).unwrap();
+        assert_eq!(result.method, Method::String("test_method".to_owned()));
+        assert_eq!(result.params.get(), r#"{"b":1, "a":[1,  2]}"#);
+
+        let result = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, input);
+    }
 }
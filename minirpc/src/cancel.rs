@@ -0,0 +1,146 @@
+//! MINI-RPC Cancellation.
+//!
+//! A caller cancels an outstanding call by sending a [`CANCEL`] notification whose params
+//! hold the call `id`. The server signals it to the handler through the
+//! [`CancellationToken`] of its `Context`, which long-running handlers poll to stop
+//! early, and answers the call with `Error::new_cancelled()`.
+//!
+//! Only the peer which made a call may cancel it: calls are tracked per `Connection`, as
+//! inserted in the `Context` by the transport, and calls received without one cannot be
+//! cancelled.
+
+use crate::context::Connection;
+use crate::Id;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Method of the notification cancelling a call.
+pub const CANCEL: &str = "$/cancel";
+
+/// Cancellation signal of a call.
+///
+/// Clones share the same signal.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a new, uncancelled `CancellationToken`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signals the cancellation.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the cancellation was signaled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Tokens of the calls in flight, by connection.
+///
+/// Calls sharing an id on a connection are tracked, and cancelled, together.
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+    tokens: Mutex<HashMap<(Connection, Id), Vec<CancellationToken>>>,
+}
+
+impl Registry {
+    /// Returns the token of call `id`, tracked until removed if received on a connection.
+    pub(crate) fn insert(&self, connection: Option<Connection>, id: Id) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(connection) = connection {
+            let mut tokens = self.tokens.lock().unwrap();
+            tokens
+                .entry((connection, id))
+                .or_default()
+                .push(token.clone());
+        }
+        token
+    }
+
+    /// Stops tracking the call `id` given `token`.
+    pub(crate) fn remove(&self, connection: Option<Connection>, id: Id, token: &CancellationToken) {
+        let connection = match connection {
+            Some(connection) => connection,
+            None => return,
+        };
+        let mut tokens = self.tokens.lock().unwrap();
+        let key = (connection, id);
+        if let Some(entries) = tokens.get_mut(&key) {
+            entries.retain(|entry| !Arc::ptr_eq(&entry.cancelled, &token.cancelled));
+            if entries.is_empty() {
+                tokens.remove(&key);
+            }
+        }
+    }
+
+    /// Cancels call `id`, returning `true` if it is in flight.
+    pub(crate) fn cancel(&self, connection: Option<Connection>, id: Id) -> bool {
+        let connection = match connection {
+            Some(connection) => connection,
+            None => return false,
+        };
+        let tokens = self.tokens.lock().unwrap();
+        match tokens.get(&(connection, id)) {
+            Some(entries) => {
+                entries.iter().for_each(CancellationToken::cancel);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_cancel() {
+        let registry = Registry::default();
+        let token = registry.insert(Some(Connection(1)), Id::Number(1));
+
+        assert!(!registry.cancel(Some(Connection(2)), Id::Number(1)));
+        assert!(!token.is_cancelled());
+
+        assert!(registry.cancel(Some(Connection(1)), Id::Number(1)));
+        assert!(token.is_cancelled());
+
+        registry.remove(Some(Connection(1)), Id::Number(1), &token);
+        assert!(!registry.cancel(Some(Connection(1)), Id::Number(1)));
+    }
+
+    #[test]
+    fn registry_duplicate() {
+        let registry = Registry::default();
+        let first = registry.insert(Some(Connection(1)), Id::Number(1));
+        let second = registry.insert(Some(Connection(1)), Id::Number(1));
+
+        // Removing a call keeps tracking the other one.
+        registry.remove(Some(Connection(1)), Id::Number(1), &first);
+        assert!(registry.cancel(Some(Connection(1)), Id::Number(1)));
+        assert!(!first.is_cancelled());
+        assert!(second.is_cancelled());
+
+        registry.remove(Some(Connection(1)), Id::Number(1), &second);
+        assert!(registry.tokens.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn registry_without_connection() {
+        let registry = Registry::default();
+        let token = registry.insert(None, Id::Number(1));
+
+        // Any peer could claim a call received without a connection.
+        assert!(!registry.cancel(None, Id::Number(1)));
+        assert!(!token.is_cancelled());
+        assert!(registry.tokens.lock().unwrap().is_empty());
+    }
+}
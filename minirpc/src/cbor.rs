@@ -0,0 +1,54 @@
+//! MINI-RPC CBOR encoding.
+
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::io;
+
+/// CBOR decoding error.
+pub type DecodeError = ciborium::de::Error<io::Error>;
+
+/// CBOR encoding error.
+pub type EncodeError = ciborium::ser::Error<io::Error>;
+
+/// Serializes given `value` as CBOR.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Deserializes an instance of `T` from CBOR `bytes`.
+pub fn from_slice<T>(bytes: &[u8]) -> Result<T, DecodeError>
+where
+    T: DeserializeOwned,
+{
+    ciborium::de::from_reader(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Id, Success};
+    use serde_json::Value;
+
+    #[test]
+    fn cbor_named_fields() {
+        let input = Success {
+            id: Id::Number(1),
+            result: Value::Bool(true),
+        };
+        // map(2), "id", 1, "result", true.
+        let expected = vec![
+            0xa2, 0x62, b'i', b'd', 0x01, 0x66, b'r', b'e', b's', b'u', b'l', b't', 0xf5,
+        ];
+
+        let result = to_vec(&input).unwrap();
+        assert_eq!(result, expected);
+
+        let result: Success = from_slice(&expected).unwrap();
+        assert_eq!(result, input);
+    }
+}
@@ -0,0 +1,180 @@
+//! MINI-RPC Chunked Results.
+//!
+//! A method registered with [`stream`] yields the items of its result one at a time
+//! instead of building a single `Value`. Each item is sent as a [`CHUNK`] notification
+//! whose params hold the call `id` and the `item`, through the `Outbox` of the
+//! connection, and the call is then answered with the number of items sent, or with
+//! the failure of the item which failed.
+//!
+//! Items are pulled one after the other as the outbox accepts them, so a bounded
+//! outbox applies backpressure up to the handler. Streaming stops early when the call
+//! is cancelled, its deadline passes, or the outbox is closed.
+
+use crate::cancel::CancellationToken;
+use crate::context::Outbox;
+use crate::server::Handler;
+use crate::shape;
+use crate::{Context, Error, Id, Method, Notification, Params};
+use serde_json::Value;
+
+/// Method of the notification carrying an item of a chunked result.
+pub const CHUNK: &str = "$/chunk";
+
+/// Sender of the items of a chunked result.
+#[derive(Clone, Debug)]
+pub struct Chunks {
+    id: Id,
+    outbox: Outbox,
+}
+
+impl Chunks {
+    /// Creates a new `Chunks` sending the items of the result of call `id` through
+    /// `outbox`.
+    pub fn new(id: Id, outbox: Outbox) -> Self {
+        Self { id, outbox }
+    }
+
+    /// Sends given `item`, returning `false` once the peer is gone.
+    pub fn send(&self, item: Value) -> bool {
+        self.outbox.send(Notification {
+            method: Method::String(CHUNK.to_owned()),
+            params: shape::CHUNK.params(&self.id, item),
+        })
+    }
+}
+
+/// Handler streaming the items yielded by a function.
+#[derive(Clone, Debug)]
+pub struct Stream<F> {
+    function: F,
+}
+
+/// Returns a handler streaming the items yielded by `function` as a chunked result.
+pub fn stream<F, I>(function: F) -> Stream<F>
+where
+    F: Fn(&Context, Params) -> Result<I, Error> + Send + Sync,
+    I: IntoIterator<Item = Result<Value, Error>>,
+{
+    Stream { function }
+}
+
+impl<F, I> Handler for Stream<F>
+where
+    F: Fn(&Context, Params) -> Result<I, Error> + Send + Sync,
+    I: IntoIterator<Item = Result<Value, Error>>,
+{
+    fn handle(&self, context: &Context, params: Params) -> Result<Value, Error> {
+        let chunks = context
+            .get::<Chunks>()
+            .ok_or_else(Error::new_internal_error)?;
+        let token = context.get::<CancellationToken>();
+
+        let mut count = 0u64;
+        for item in (self.function)(context, params)? {
+            if !chunks.send(item?) {
+                break;
+            }
+            count += 1;
+
+            if token.is_some_and(CancellationToken::is_cancelled) || context.is_expired() {
+                break;
+            }
+        }
+        Ok(Value::from(count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Server;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn chunk_stream() {
+        let mut server = Server::new();
+        server
+            .register(
+                "range",
+                stream(|_: &Context, _params| Ok((0..3).map(|i| Ok(Value::from(i))))),
+            )
+            .unwrap();
+        server
+            .register(
+                "fail",
+                stream(|_: &Context, _params| {
+                    Ok(vec![Ok(Value::from(0)), Err(Error::new_internal_error())])
+                }),
+            )
+            .unwrap();
+
+        // A single slot: the handler waits for each chunk to be taken.
+        let (outbox, chunks) = Outbox::bounded(1);
+        let mut context = Context::new();
+        context.insert(outbox);
+        let call = thread::spawn(move || {
+            let input = r#"{"id":1,"method":"range","params":[]}"#;
+            let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+
+            let input = r#"{"id":2,"method":"fail","params":[]}"#;
+            let failure = server.handle_with(&context, serde_json::from_str(input).unwrap());
+            (result, failure)
+        });
+
+        let expected = vec![
+            r#"{"method":"$/chunk","params":{"id":1,"item":0}}"#,
+            r#"{"method":"$/chunk","params":{"id":1,"item":1}}"#,
+            r#"{"method":"$/chunk","params":{"id":1,"item":2}}"#,
+            r#"{"method":"$/chunk","params":{"id":2,"item":0}}"#,
+        ];
+        let result: Vec<_> = chunks
+            .iter()
+            .map(|notification| serde_json::to_string(&notification).unwrap())
+            .collect();
+        assert_eq!(result, expected);
+
+        let (result, failure) = call.join().unwrap();
+        let expected = r#"{"id":1,"result":3}"#;
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+        let expected = r#"{"error":{"code":-32603,"message":"Internal error"},"id":2}"#;
+        assert_eq!(serde_json::to_string(&failure).unwrap(), expected);
+    }
+
+    #[test]
+    fn chunk_closed() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let mut server = Server::new();
+        let counter = Arc::clone(&pulled);
+        server
+            .register(
+                "range",
+                stream(move |_: &Context, _params| {
+                    let counter = Arc::clone(&counter);
+                    Ok((0..100).map(move |i| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(Value::from(i))
+                    }))
+                }),
+            )
+            .unwrap();
+
+        // The receiver goes away after the first chunk: the stream stops pulling items.
+        let (outbox, chunks) = Outbox::bounded(0);
+        let mut context = Context::new();
+        context.insert(outbox);
+        let call = thread::spawn(move || {
+            let input = r#"{"id":1,"method":"range","params":[]}"#;
+            server.handle_with(&context, serde_json::from_str(input).unwrap())
+        });
+
+        assert!(chunks.recv().is_ok());
+        drop(chunks);
+
+        let result = call.join().unwrap();
+        let expected = r#"{"id":1,"result":1}"#;
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+        assert_eq!(pulled.load(Ordering::SeqCst), 2);
+    }
+}
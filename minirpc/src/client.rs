@@ -0,0 +1,924 @@
+//! MINI-RPC Client.
+//!
+//! A [`Client`] builds `Call`s and `Notification`s and correlates incoming responses
+//! with the pending calls. It is transport agnostic: send what [`Client::call`] and
+//! [`Client::notify`] return, and feed what is received to [`Client::receive`].
+//!
+//! Calls resolve with `Error::new_timeout()` once their deadline passes, either set on
+//! the `Context` given to [`Client::call_with`] or derived from the default timeout.
+//! Expired calls are purged when waited for or by [`Client::expire`], after which late
+//! responses are ignored. [`Client::cancel`] likewise gives up on a call, returning the
+//! `$/cancel` notification asking the server to stop working on it.
+//!
+//! Notifications sent by the server are fed to [`Client::receive_notification`], which
+//! forwards `$/progress` reports to the [`Reply`] of the call they report on, and
+//! `$/subscription` events to the [`Subscription`] they belong to. Events received
+//! before their stream is created are kept, up to a bound beyond which the oldest are
+//! dropped; events of subscriptions which ended, being unsubscribed or their stream
+//! dropped, are dropped.
+//!
+//! [`Client::call_chunked`] makes a call whose result is streamed in `$/chunk`
+//! notifications, consumed through [`Chunked`]. At most the chunk capacity of items are
+//! queued: beyond, [`Client::receive_notification`] blocks until the consumer catches
+//! up, so that the transport stops reading from the server.
+
+use crate::cancel::CANCEL;
+use crate::chunk::CHUNK;
+use crate::interceptor::{Chain, Interceptor};
+use crate::progress::PROGRESS;
+use crate::shape;
+use crate::subscription::SUBSCRIPTION;
+use crate::{
+    Call, Context, Error, Failure, Id, Method, Notification, Params, Response, ResponsePayload,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Tracks calls awaiting a response.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Mutex<Inner>>,
+    interceptors: Chain,
+    timeout: Option<Duration>,
+    strict: bool,
+    chunk_capacity: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    pending: HashMap<Id, Pending>,
+    next_stream: u64,
+    subscriptions: HashMap<Id, (u64, Sender<Value>)>,
+    early: VecDeque<(Id, Value)>,
+    ended: VecDeque<Id>,
+}
+
+/// Maximum number of events kept for subscriptions not streamed yet, as the server may
+/// send events before the answer to the subscribe call.
+const MAX_EARLY_EVENTS: usize = 256;
+
+/// Maximum number of ended subscriptions whose late events are recognized as such.
+const MAX_ENDED: usize = 256;
+
+impl Inner {
+    /// Ends subscription `id`, dropping its kept and late events.
+    fn end(&mut self, id: &Id) {
+        self.subscriptions.remove(id);
+        self.early.retain(|(early, _)| early != id);
+        if self.ended.len() == MAX_ENDED {
+            self.ended.pop_front();
+        }
+        self.ended.push_back(id.clone());
+    }
+}
+
+/// Default number of items of a chunked result queued before applying backpressure.
+pub const DEFAULT_CHUNK_CAPACITY: usize = 64;
+
+struct Pending {
+    context: Context,
+    sender: Sender<Result<Value, Error>>,
+    progress: Sender<Value>,
+    chunks: Option<SyncSender<Value>>,
+}
+
+impl Pending {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.context
+            .deadline()
+            .is_some_and(|deadline| now >= deadline)
+    }
+}
+
+impl Client {
+    /// Creates a new `Client` without pending call.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends given `interceptor` to the ones run around each invocation.
+    pub fn intercept<I>(&mut self, interceptor: I)
+    where
+        I: Interceptor + 'static,
+    {
+        self.interceptors.push(interceptor);
+    }
+
+    /// Sets the timeout of the calls made without a deadline.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Sets whether calls are kept to the standard envelope (the default), or carry
+    /// their deadline for non-strict servers to honor.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Sets the number of items of a chunked result queued before applying backpressure.
+    pub fn set_chunk_capacity(&mut self, capacity: usize) {
+        self.chunk_capacity = capacity;
+    }
+
+    /// Builds a `Call` of `method` with given `params`, to be sent to the server.
+    ///
+    /// The returned `Reply` resolves once the matching response is received.
+    pub fn call(&self, method: &str, params: Params) -> Result<(Call, Reply), Error> {
+        self.call_with(Context::new(), method, params)
+    }
+
+    /// Builds a `Call` like [`Client::call`], running the interceptors with given
+    /// `context`, which is kept until the call resolves.
+    ///
+    /// The call times out at the context deadline, if any, or after the default timeout.
+    pub fn call_with(
+        &self,
+        context: Context,
+        method: &str,
+        params: Params,
+    ) -> Result<(Call, Reply), Error> {
+        self.start(context, method, params, None)
+    }
+
+    /// Builds a `Call` like [`Client::call`], of a method whose result is streamed.
+    pub fn call_chunked<T>(&self, method: &str, params: Params) -> Result<(Call, Chunked<T>), Error>
+    where
+        T: DeserializeOwned,
+    {
+        let (chunks, items) = mpsc::sync_channel(self.chunk_capacity);
+        let (call, reply) = self.start(Context::new(), method, params, Some(chunks))?;
+        let chunked = Chunked {
+            reply,
+            items,
+            item: PhantomData,
+        };
+        Ok((call, chunked))
+    }
+
+    /// Builds a `Notification` of `method` with given `params`, to be sent to the server.
+    pub fn notify(&self, method: &str, params: Params) -> Result<Notification, Error> {
+        let mut notification = Notification {
+            method: Method::String(method.to_owned()),
+            params,
+        };
+        self.interceptors
+            .before_notification(&mut Context::new(), &mut notification)?;
+        Ok(notification)
+    }
+
+    /// Resolves the pending call `id` with a cancellation error, and builds the
+    /// `Notification` cancelling it, to be sent to the server.
+    pub fn cancel(&self, id: &Id) -> Result<Notification, Error> {
+        let pending = self.inner.lock().unwrap().pending.remove(id);
+        if let Some(pending) = pending {
+            self.fail(id.clone(), pending, Error::new_cancelled());
+        }
+        self.notify(CANCEL, shape::CANCEL.params(id, Value::Null))
+    }
+
+    /// Resolves the pending calls answered by given `response`.
+    ///
+    /// Payloads which do not match a pending call are ignored.
+    pub fn receive(&self, response: Response) {
+        match response {
+            Response::Single(payload) => self.resolve(payload),
+            Response::Batch(payloads) => payloads
+                .into_iter()
+                .for_each(|payload| self.resolve(payload)),
+        }
+    }
+
+    /// Returns the stream of the events of subscription `id`, as answered by the server
+    /// to a subscribe call.
+    pub fn subscription<T>(&self, id: Id) -> Subscription<T>
+    where
+        T: DeserializeOwned,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let mut inner = self.inner.lock().unwrap();
+        inner.early.retain(|(early, value)| {
+            if *early == id {
+                let _ = sender.send(value.clone());
+                return false;
+            }
+            true
+        });
+        // The server may reuse the id of an ended subscription.
+        inner.ended.retain(|ended| *ended != id);
+        let stream = inner.next_stream;
+        inner.next_stream += 1;
+        inner.subscriptions.insert(id.clone(), (stream, sender));
+
+        Subscription {
+            id,
+            stream,
+            receiver,
+            inner: Arc::clone(&self.inner),
+            result: PhantomData,
+        }
+    }
+
+    /// Ends the stream of subscription `id`, and builds the `Call` of the unsubscribe
+    /// `method`, to be sent to the server.
+    pub fn unsubscribe(&self, method: &str, id: &Id) -> Result<(Call, Reply), Error> {
+        self.inner.lock().unwrap().end(id);
+        self.call(method, shape::UNSUBSCRIBE.params(id, Value::Null))
+    }
+
+    /// Forwards given `notification` received from the server to the pending call or
+    /// the subscription it belongs to, returning it if it is neither a `$/progress`
+    /// report, a `$/chunk` item nor a `$/subscription` event.
+    ///
+    /// Reports and items of calls which are no longer pending are dropped, like events
+    /// of ended subscriptions.
+    pub fn receive_notification(&self, notification: Notification) -> Option<Notification> {
+        let method = notification.method.as_str();
+        let parsed = match method {
+            PROGRESS => shape::PROGRESS.parse(notification.params.clone()),
+            CHUNK => shape::CHUNK.parse(notification.params.clone()),
+            SUBSCRIPTION => shape::SUBSCRIPTION.parse(notification.params.clone()),
+            _ => return Some(notification),
+        };
+        let (id, value) = match parsed {
+            Ok(parsed) => parsed,
+            Err(_) => return Some(notification),
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        if method == PROGRESS {
+            if let Some(pending) = inner.pending.get(&id) {
+                let _ = pending.progress.send(value);
+            }
+        } else if method == CHUNK {
+            let chunks = inner
+                .pending
+                .get(&id)
+                .and_then(|pending| pending.chunks.clone());
+            // Not to block the other calls while applying backpressure.
+            drop(inner);
+            if let Some(chunks) = chunks {
+                let _ = chunks.send(value);
+            }
+        } else if let Some((_, sender)) = inner.subscriptions.get(&id) {
+            let _ = sender.send(value);
+        } else if !inner.ended.contains(&id) {
+            // Events of subscriptions never streamed do not crowd out the newest ones.
+            if inner.early.len() == MAX_EARLY_EVENTS {
+                inner.early.pop_front();
+            }
+            inner.early.push_back((id, value));
+        }
+        None
+    }
+
+    /// Returns the number of calls awaiting a response.
+    pub fn pending(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// Resolves the pending calls whose deadline passed with a timeout error, returning
+    /// how many expired.
+    pub fn expire(&self) -> usize {
+        let now = Instant::now();
+        let expired: Vec<_> = {
+            let mut inner = self.inner.lock().unwrap();
+            let ids: Vec<_> = inner
+                .pending
+                .iter()
+                .filter(|(_, pending)| pending.is_expired(now))
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| inner.pending.remove_entry(&id))
+                .collect()
+        };
+
+        let count = expired.len();
+        for (id, pending) in expired {
+            self.fail(id, pending, Error::new_timeout());
+        }
+        count
+    }
+
+    fn start(
+        &self,
+        mut context: Context,
+        method: &str,
+        params: Params,
+        chunks: Option<SyncSender<Value>>,
+    ) -> Result<(Call, Reply), Error> {
+        if let (None, Some(timeout)) = (context.deadline(), self.timeout) {
+            if let Some(deadline) = Instant::now().checked_add(timeout) {
+                context.set_deadline(deadline);
+            }
+        }
+        let deadline = context.deadline();
+
+        let mut call = Call {
+            id: self.next_id(),
+            method: Method::String(method.to_owned()),
+            params,
+            deadline: match (self.strict, deadline) {
+                (false, Some(deadline)) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    Some(remaining.as_millis() as u64)
+                }
+                _ => None,
+            },
+        };
+        self.interceptors
+            .before_call(&mut context, &mut call)
+            .map_err(|failure| failure.error)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let (progress, reports) = mpsc::channel();
+        let pending = Pending {
+            context,
+            sender,
+            progress,
+            chunks,
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .pending
+            .insert(call.id.clone(), pending);
+        let reply = Reply {
+            id: call.id.clone(),
+            receiver,
+            reports,
+            deadline,
+            client: self.clone(),
+        };
+        Ok((call, reply))
+    }
+
+    fn next_id(&self) -> Id {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        Id::Number(inner.next_id)
+    }
+
+    fn resolve(&self, payload: ResponsePayload) {
+        let id = match &payload {
+            ResponsePayload::Success(success) => &success.id,
+            ResponsePayload::Failure(failure) => match &failure.id {
+                Some(id) => id,
+                None => return,
+            },
+        };
+        let pending = match self.inner.lock().unwrap().pending.remove(id) {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        self.settle(pending, payload);
+    }
+
+    fn fail(&self, id: Id, pending: Pending, error: Error) {
+        let payload = ResponsePayload::Failure(Failure {
+            error,
+            id: Some(id),
+        });
+        self.settle(pending, payload);
+    }
+
+    fn settle(&self, pending: Pending, mut payload: ResponsePayload) {
+        self.interceptors.after(&pending.context, &mut payload);
+        let _ = pending.sender.send(match payload {
+            ResponsePayload::Success(success) => Ok(success.result),
+            ResponsePayload::Failure(failure) => Err(failure.error),
+        });
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            interceptors: Chain::new(),
+            timeout: None,
+            strict: true,
+            chunk_capacity: DEFAULT_CHUNK_CAPACITY,
+        }
+    }
+}
+
+/// Pending call result.
+#[must_use]
+pub struct Reply {
+    id: Id,
+    receiver: Receiver<Result<Value, Error>>,
+    reports: Receiver<Value>,
+    deadline: Option<Instant>,
+    client: Client,
+}
+
+impl Reply {
+    /// Returns the id of the call.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Returns the instant at which the call times out, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Blocks until the call resolves or times out.
+    pub fn wait(self) -> Result<Value, Error> {
+        if let Some(deadline) = self.deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.receiver.recv_timeout(timeout) {
+                Ok(result) => return result,
+                Err(RecvTimeoutError::Timeout) => self.expire(),
+                Err(RecvTimeoutError::Disconnected) => return Err(Error::new_internal_error()),
+            }
+        }
+
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(Error::new_internal_error()))
+    }
+
+    /// Returns the call result if it already resolved or timed out.
+    pub fn try_wait(&self) -> Option<Result<Value, Error>> {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.expire();
+        }
+
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(Error::new_internal_error())),
+        }
+    }
+
+    /// Returns the reply decoding the call result as `T`.
+    pub fn typed<T>(self) -> Typed<T>
+    where
+        T: DeserializeOwned,
+    {
+        Typed {
+            reply: self,
+            result: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the progress reports of the call, which blocks until the
+    /// next report and ends once the call resolves or times out.
+    pub fn progress(&self) -> Reports<'_> {
+        Reports { reply: self }
+    }
+
+    /// Blocks until the next message of `receiver`, which is disconnected once the call
+    /// resolves, or until the call times out.
+    fn recv<T>(&self, receiver: &Receiver<T>) -> Option<T> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return receiver.recv().ok(),
+        };
+
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => {
+                self.expire();
+                receiver.try_recv().ok()
+            }
+            Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Times the call out, unless it resolved meanwhile.
+    fn expire(&self) {
+        let pending = self.client.inner.lock().unwrap().pending.remove(&self.id);
+        if let Some(pending) = pending {
+            self.client
+                .fail(self.id.clone(), pending, Error::new_timeout());
+        }
+    }
+}
+
+impl fmt::Debug for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Reply")
+            .field("id", &self.id)
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+/// Pending call result, decoded as `T`.
+///
+/// Results which cannot be decoded resolve with `Error::new_parse_error()`, whose data
+/// holds the reason.
+#[must_use]
+#[derive(Debug)]
+pub struct Typed<T> {
+    reply: Reply,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T> Typed<T>
+where
+    T: DeserializeOwned,
+{
+    /// Returns the id of the call.
+    pub fn id(&self) -> &Id {
+        self.reply.id()
+    }
+
+    /// Blocks until the call resolves or times out.
+    pub fn wait(self) -> Result<T, Error> {
+        self.reply.wait().and_then(decode)
+    }
+
+    /// Returns the call result if it already resolved or timed out.
+    pub fn try_wait(&self) -> Option<Result<T, Error>> {
+        self.reply.try_wait().map(|result| result.and_then(decode))
+    }
+}
+
+/// Iterator over the progress reports of a call.
+#[derive(Debug)]
+pub struct Reports<'a> {
+    reply: &'a Reply,
+}
+
+impl Iterator for Reports<'_> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        self.reply.recv(&self.reply.reports)
+    }
+}
+
+/// Stream of the items of a chunked result.
+///
+/// Like results, items which cannot be decoded yield `Error::new_parse_error()`.
+#[must_use]
+#[derive(Debug)]
+pub struct Chunked<T> {
+    reply: Reply,
+    items: Receiver<Value>,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Chunked<T>
+where
+    T: DeserializeOwned,
+{
+    /// Returns the id of the call.
+    pub fn id(&self) -> &Id {
+        self.reply.id()
+    }
+
+    /// Blocks until the call resolves or times out, returning the number of items the
+    /// server sent on success. Items not consumed yet are discarded.
+    pub fn finish(self) -> Result<Value, Error> {
+        // Not to keep the transport blocked on a full queue.
+        drop(self.items);
+        self.reply.wait()
+    }
+}
+
+/// Blocks until the next item, ending once the call resolves or times out.
+impl<T> Iterator for Chunked<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reply.recv(&self.items).map(decode)
+    }
+}
+
+/// Stream of the events of a subscription.
+///
+/// Like results, events which cannot be decoded yield `Error::new_parse_error()`.
+/// Dropping the stream ends the subscription on the client side.
+pub struct Subscription<T> {
+    id: Id,
+    stream: u64,
+    receiver: Receiver<Value>,
+    inner: Arc<Mutex<Inner>>,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T> Subscription<T>
+where
+    T: DeserializeOwned,
+{
+    /// Returns the subscription id.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Returns the next event if one was already received.
+    pub fn try_next(&self) -> Option<Result<T, Error>> {
+        self.receiver.try_recv().ok().map(decode)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        // Unless the subscription already ended, or was streamed again.
+        if let Some((stream, _)) = inner.subscriptions.get(&self.id) {
+            if *stream == self.stream {
+                inner.end(&self.id);
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Blocks until the next event, ending once the client unsubscribed.
+impl<T> Iterator for Subscription<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok().map(decode)
+    }
+}
+
+/// Decodes a result, an item or an event received from the server.
+fn decode<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_value(value)
+        .map_err(|err| Error::new_parse_error().with_data(Value::from(err.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Code;
+    use crate::Success;
+
+    #[test]
+    fn client_call() {
+        let client = Client::new();
+        let (call, reply) = client
+            .call("sum", Params::Array(vec![1.into(), 2.into()]))
+            .unwrap();
+
+        let expected = r#"{"id":1,"method":"sum","params":[1,2]}"#;
+        assert_eq!(serde_json::to_string(&call).unwrap(), expected);
+        assert_eq!(reply.try_wait(), None);
+        assert_eq!(client.pending(), 1);
+
+        // Unknown ids are ignored.
+        client.receive(serde_json::from_str(r#"{"id":2,"result":3}"#).unwrap());
+        assert_eq!(reply.try_wait(), None);
+
+        client.receive(serde_json::from_str(r#"{"id":1,"result":3}"#).unwrap());
+        assert_eq!(reply.wait(), Ok(Value::from(3)));
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[test]
+    fn client_receive_batch() {
+        let client = Client::new();
+        let (_, first) = client.call("sum", Params::Array(vec![])).unwrap();
+        let (_, second) = client.call("sum", Params::Array(vec![])).unwrap();
+
+        client.receive(Response::Batch(vec![
+            ResponsePayload::Failure(Failure {
+                error: Error::new_invalid_params(),
+                id: Some(Id::Number(2)),
+            }),
+            ResponsePayload::Success(Success {
+                id: Id::Number(1),
+                result: Value::from(0),
+            }),
+        ]));
+        assert_eq!(first.wait(), Ok(Value::from(0)));
+        assert_eq!(second.wait(), Err(Error::new_invalid_params()));
+    }
+
+    #[test]
+    fn client_intercept() {
+        struct Deny;
+
+        impl Interceptor for Deny {
+            fn before_call(&self, _context: &mut Context, call: &mut Call) -> Result<(), Error> {
+                if call.method.as_str() == "admin" {
+                    return Err(Error::new_server_error(-32000, "Denied"));
+                }
+                Ok(())
+            }
+
+            fn after_success(&self, _context: &Context, success: &mut Success) {
+                success.result = Value::Bool(true);
+            }
+        }
+
+        let mut client = Client::new();
+        client.intercept(Deny);
+
+        let result = client.call("admin", Params::Array(vec![]));
+        assert_eq!(result.unwrap_err().message, "Denied");
+        assert_eq!(client.pending(), 0);
+
+        let (_, reply) = client.call("sum", Params::Array(vec![])).unwrap();
+        client.receive(serde_json::from_str(r#"{"id":2,"result":3}"#).unwrap());
+        assert_eq!(reply.wait(), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn client_timeout() {
+        let mut client = Client::new();
+        client.set_timeout(Duration::from_millis(10));
+        let (_, reply) = client.call("sum", Params::Array(vec![])).unwrap();
+
+        assert_eq!(reply.wait(), Err(Error::new_timeout()));
+        assert_eq!(client.pending(), 0);
+
+        // Late responses are ignored.
+        client.receive(serde_json::from_str(r#"{"id":1,"result":3}"#).unwrap());
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[test]
+    fn client_expire() {
+        let client = Client::new();
+        let mut context = Context::new();
+        context.set_deadline(Instant::now());
+        let (_, expired) = client
+            .call_with(context, "sum", Params::Array(vec![]))
+            .unwrap();
+        let (_, reply) = client.call("sum", Params::Array(vec![])).unwrap();
+
+        assert_eq!(client.expire(), 1);
+        assert_eq!(client.pending(), 1);
+        assert_eq!(expired.try_wait(), Some(Err(Error::new_timeout())));
+        assert_eq!(reply.deadline(), None);
+        assert_eq!(reply.try_wait(), None);
+    }
+
+    #[test]
+    fn client_deadline() {
+        let mut client = Client::new();
+        client.set_timeout(Duration::from_secs(60));
+
+        let (call, _) = client.call("sum", Params::Array(vec![])).unwrap();
+        assert_eq!(call.deadline, None);
+
+        client.set_strict(false);
+        let (call, _) = client.call("sum", Params::Array(vec![])).unwrap();
+        assert!(call.deadline.unwrap() > 30_000);
+    }
+
+    #[test]
+    fn client_cancel() {
+        let client = Client::new();
+        let (call, reply) = client.call("sum", Params::Array(vec![])).unwrap();
+
+        let notification = client.cancel(&call.id).unwrap();
+        let expected = r#"{"method":"$/cancel","params":{"id":1}}"#;
+        assert_eq!(serde_json::to_string(&notification).unwrap(), expected);
+        assert_eq!(reply.wait(), Err(Error::new_cancelled()));
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[test]
+    fn client_progress() {
+        let client = Client::new();
+        let (_, reply) = client.call("count", Params::Array(vec![])).unwrap();
+
+        for value in 1..=2 {
+            let input = format!(
+                r#"{{"method":"$/progress","params":{{"id":1,"value":{}}}}}"#,
+                value
+            );
+            let result = client.receive_notification(serde_json::from_str(&input).unwrap());
+            assert_eq!(result, None);
+        }
+        let input = r#"{"method":"log","params":[]}"#;
+        let result = client.receive_notification(serde_json::from_str(input).unwrap());
+        assert!(result.is_some());
+
+        client.receive(serde_json::from_str(r#"{"id":1,"result":2}"#).unwrap());
+        let result: Vec<_> = reply.progress().collect();
+        assert_eq!(result, vec![Value::from(1), Value::from(2)]);
+        assert_eq!(reply.wait(), Ok(Value::from(2)));
+    }
+
+    #[test]
+    fn client_subscription() {
+        let client = Client::new();
+        let event = |id: u64, result: &str| {
+            let input = format!(
+                r#"{{"method":"$/subscription","params":{{"subscription":{},"result":{}}}}}"#,
+                id, result
+            );
+            client.receive_notification(serde_json::from_str(&input).unwrap())
+        };
+
+        // Events received before the stream is created are kept.
+        assert_eq!(event(1, "10"), None);
+        let mut ticks = client.subscription::<u64>(Id::Number(1));
+        assert_eq!(event(1, "11"), None);
+        assert_eq!(event(1, "\"twelve\""), None);
+        assert_eq!(event(2, "0"), None);
+
+        assert_eq!(ticks.next(), Some(Ok(10)));
+        assert_eq!(ticks.next(), Some(Ok(11)));
+        let error = ticks.next().unwrap().unwrap_err();
+        assert_eq!(error.code, Code::ParseError);
+        let expected = "invalid type: string \"twelve\", expected u64";
+        assert_eq!(error.data, Some(Value::from(expected)));
+        assert_eq!(ticks.try_next(), None);
+
+        let (call, _) = client.unsubscribe("unsubscribe", ticks.id()).unwrap();
+        let expected = r#"{"id":1,"method":"unsubscribe","params":[1]}"#;
+        assert_eq!(serde_json::to_string(&call).unwrap(), expected);
+        assert_eq!(ticks.next(), None);
+
+        // Late events of ended subscriptions are not kept.
+        assert_eq!(event(1, "13"), None);
+        let ticks = client.subscription::<u64>(Id::Number(1));
+        assert_eq!(ticks.try_next(), None);
+
+        // Nor those of dropped streams.
+        drop(ticks);
+        assert_eq!(event(1, "14"), None);
+        let inner = client.inner.lock().unwrap();
+        assert!(inner.early.iter().all(|(id, _)| *id == Id::Number(2)));
+        drop(inner);
+
+        // The newest events are kept first.
+        for tick in 0..=MAX_EARLY_EVENTS {
+            assert_eq!(event(3, &tick.to_string()), None);
+        }
+        let ticks = client.subscription::<usize>(Id::Number(3));
+        assert_eq!(ticks.try_next(), Some(Ok(1)));
+    }
+
+    #[test]
+    fn client_call_chunked() {
+        let mut client = Client::new();
+        client.set_chunk_capacity(1);
+        let (_, mut chunked) = client
+            .call_chunked::<String>("lines", Params::Array(vec![]))
+            .unwrap();
+
+        let receiver = client.clone();
+        let server = std::thread::spawn(move || {
+            for item in &["a", "b"] {
+                let input = format!(
+                    r#"{{"method":"$/chunk","params":{{"id":1,"item":"{}"}}}}"#,
+                    item
+                );
+                receiver.receive_notification(serde_json::from_str(&input).unwrap());
+            }
+            receiver.receive(serde_json::from_str(r#"{"id":1,"result":2}"#).unwrap());
+        });
+
+        assert_eq!(chunked.next(), Some(Ok("a".to_owned())));
+        assert_eq!(chunked.next(), Some(Ok("b".to_owned())));
+        assert_eq!(chunked.next(), None);
+        assert_eq!(chunked.finish(), Ok(Value::from(2)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn client_typed() {
+        let client = Client::new();
+        let (_, first) = client.call("sum", Params::Array(vec![])).unwrap();
+        let (_, second) = client.call("sum", Params::Array(vec![])).unwrap();
+        let (first, second) = (first.typed::<u64>(), second.typed::<u64>());
+
+        client.receive(serde_json::from_str(r#"{"id":1,"result":3}"#).unwrap());
+        client.receive(serde_json::from_str(r#"{"id":2,"result":"3"}"#).unwrap());
+        assert_eq!(first.wait(), Ok(3));
+        let error = second.try_wait().unwrap().unwrap_err();
+        assert_eq!(error.code, Code::ParseError);
+        let expected = "invalid type: string \"3\", expected u64";
+        assert_eq!(error.data, Some(Value::from(expected)));
+    }
+}
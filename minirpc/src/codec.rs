@@ -0,0 +1,284 @@
+//! MINI-RPC Codecs.
+//!
+//! A [`Codec`] turns messages into bytes and back, so framing and dispatch layers can
+//! stay independent of the wire format. [`Format`] selects a built-in codec at runtime:
+//! JSON, MessagePack (`msgpack` feature) or CBOR (`cbor` feature).
+
+use crate::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Codec error.
+#[derive(Debug)]
+pub enum Error {
+    /// The value could not be encoded.
+    Encode(Box<dyn StdError + Send + Sync>),
+
+    /// The bytes could not be decoded.
+    Decode(Box<dyn StdError + Send + Sync>),
+
+    /// The format name is unknown or its feature is disabled.
+    UnknownFormat(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Encode(err) => write!(f, "Encode error: {}", err),
+            Error::Decode(err) => write!(f, "Decode error: {}", err),
+            Error::UnknownFormat(name) => write!(f, "Unknown format: {}", name),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+/// Message codec.
+pub trait Codec {
+    /// Serializes given `value`.
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize + ?Sized;
+
+    /// Deserializes an instance of `T` from `bytes`.
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned;
+
+    /// Serializes given `request`.
+    fn encode_request<P>(&self, request: &Request<P>) -> Result<Vec<u8>, Error>
+    where
+        P: Serialize,
+    {
+        self.encode(request)
+    }
+
+    /// Deserializes a request from `bytes`.
+    fn decode_request<P>(&self, bytes: &[u8]) -> Result<Request<P>, Error>
+    where
+        P: DeserializeOwned,
+    {
+        self.decode(bytes)
+    }
+
+    /// Serializes given `response`.
+    fn encode_response<R, D>(&self, response: &Response<R, D>) -> Result<Vec<u8>, Error>
+    where
+        R: Serialize,
+        D: Serialize,
+    {
+        self.encode(response)
+    }
+
+    /// Deserializes a response from `bytes`.
+    fn decode_response<R, D>(&self, bytes: &[u8]) -> Result<Response<R, D>, Error>
+    where
+        R: DeserializeOwned,
+        D: DeserializeOwned,
+    {
+        self.decode(bytes)
+    }
+}
+
+/// Wire format.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    /// JSON.
+    #[default]
+    Json,
+
+    /// MessagePack.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+
+    /// CBOR.
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl Format {
+    /// Returns the format name.
+    pub fn name(&self) -> &str {
+        match *self {
+            Format::Json => "json",
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => "msgpack",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "cbor",
+        }
+    }
+}
+
+impl Codec for Format {
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        match *self {
+            Format::Json => serde_json::to_vec(value).map_err(|err| Error::Encode(err.into())),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                crate::msgpack::to_vec(value).map_err(|err| Error::Encode(err.into()))
+            }
+            #[cfg(feature = "cbor")]
+            Format::Cbor => crate::cbor::to_vec(value).map_err(|err| Error::Encode(err.into())),
+        }
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        match *self {
+            Format::Json => serde_json::from_slice(bytes).map_err(|err| Error::Decode(err.into())),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                crate::msgpack::from_slice(bytes).map_err(|err| Error::Decode(err.into()))
+            }
+            #[cfg(feature = "cbor")]
+            Format::Cbor => crate::cbor::from_slice(bytes).map_err(|err| Error::Decode(err.into())),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "json" => Ok(Format::Json),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Ok(Format::MessagePack),
+            #[cfg(feature = "cbor")]
+            "cbor" => Ok(Format::Cbor),
+            name => Err(Error::UnknownFormat(name.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Returns the enabled formats.
+#[cfg(test)]
+pub(crate) fn formats() -> Vec<Format> {
+    vec![
+        Format::Json,
+        #[cfg(feature = "msgpack")]
+        Format::MessagePack,
+        #[cfg(feature = "cbor")]
+        Format::Cbor,
+    ]
+}
+
+/// Checks that each of the JSON `inputs`, decoded as a `T`, round-trips through every
+/// enabled format.
+#[cfg(test)]
+pub(crate) fn assert_round_trip<T>(inputs: &[&str])
+where
+    T: DeserializeOwned + Serialize + PartialEq + fmt::Debug,
+{
+    for format in formats() {
+        for input in inputs {
+            let expected: T = serde_json::from_str(input).unwrap();
+
+            let bytes = format.encode(&expected).unwrap();
+            let result: T = format.decode(&bytes).unwrap();
+            assert_eq!(result, expected, "{}", format);
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                *input,
+                "{}",
+                format
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Call, Error as RpcError, Failure, Id, Method, RequestPayload, ResponsePayload};
+
+    #[test]
+    fn format_from_str() {
+        for format in formats() {
+            let result: Format = format.name().parse().unwrap();
+            assert_eq!(result, format);
+        }
+
+        let result = "yaml".parse::<Format>();
+        assert!(matches!(result, Err(Error::UnknownFormat(ref name)) if name == "yaml"));
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let request: Request = serde_json::from_str(
+            r#"[{"method":"test_method","params":{"foo":"bar"}},{"id":1,"method":"test_method","params":[1,2,3]}]"#,
+        )
+        .unwrap();
+        let response = Response::Single(ResponsePayload::Failure(Failure {
+            error: RpcError::new_parse_error(),
+            id: None,
+        }));
+
+        for format in formats() {
+            let bytes = format.encode_request(&request).unwrap();
+            let result: Request = format.decode_request(&bytes).unwrap();
+            assert_eq!(result, request);
+
+            let bytes = format.encode_response(&response).unwrap();
+            let result: Response = format.decode_response(&bytes).unwrap();
+            assert_eq!(result, response);
+        }
+    }
+
+    #[test]
+    fn format_decode_error() {
+        for format in formats() {
+            let result = format.decode::<Request>(&[0xc1]);
+            assert!(matches!(result, Err(Error::Decode(_))));
+        }
+    }
+
+    #[test]
+    fn codec_custom() {
+        struct Pretty;
+
+        impl Codec for Pretty {
+            fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Error>
+            where
+                T: Serialize + ?Sized,
+            {
+                serde_json::to_vec_pretty(value).map_err(|err| Error::Encode(err.into()))
+            }
+
+            fn decode<T>(&self, bytes: &[u8]) -> Result<T, Error>
+            where
+                T: DeserializeOwned,
+            {
+                serde_json::from_slice(bytes).map_err(|err| Error::Decode(err.into()))
+            }
+        }
+
+        // Typed params instead of the default `Params`.
+        let request = Request::Single(RequestPayload::Call(Call {
+            id: Id::Number(1),
+            method: Method::String("sum".to_owned()),
+            params: (1, 2),
+            deadline: None,
+        }));
+
+        let bytes = Pretty.encode_request(&request).unwrap();
+        assert!(bytes.contains(&b'\n'));
+
+        let result: Request<(i64, i64)> = Pretty.decode_request(&bytes).unwrap();
+        assert_eq!(result, request);
+    }
+}
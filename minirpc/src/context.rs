@@ -0,0 +1,187 @@
+//! MINI-RPC Request Context.
+//!
+//! A [`Context`] travels alongside each `Call` and `Notification`. Transports and
+//! interceptors populate it with typed extensions (peer address, connection, identity,
+//! ...) which handlers can read, and with a deadline handlers can cooperate with.
+
+use crate::Notification;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Request context.
+///
+/// Cloning a context is cheap: extensions are shared, not copied.
+#[derive(Clone, Default)]
+pub struct Context {
+    extensions: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    deadline: Option<Instant>,
+}
+
+impl Context {
+    /// Creates a new, empty `Context`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts given extension `value`, replacing any previous one of the same type.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the extension of type `T`, if any.
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Returns `true` if the context holds an extension of type `T`.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Removes the extension of type `T`, returning `true` if there was one.
+    pub fn remove<T>(&mut self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    /// Removes the extension of type `T`, returning it if there was one.
+    pub(crate) fn take<T>(&mut self) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+    }
+
+    /// Returns the instant after which the caller no longer waits for a result.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Sets the instant after which the caller no longer waits for a result.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Returns the time left before the deadline, if any.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns `true` if the deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("extensions", &self.extensions.len())
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+/// Address of the remote peer, as reported by the transport.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Peer(pub String);
+
+/// Identifier of the connection a request was received on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Connection(pub u64);
+
+/// Sender of notifications to the remote peer, as provided by the transport.
+#[derive(Clone)]
+pub struct Outbox {
+    send: Arc<dyn Fn(Notification) -> bool + Send + Sync>,
+}
+
+impl Outbox {
+    /// Creates a new `Outbox` handing notifications to `send`, which returns `false`
+    /// once the remote peer is gone.
+    pub fn new<F>(send: F) -> Self
+    where
+        F: Fn(Notification) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            send: Arc::new(send),
+        }
+    }
+
+    /// Creates a new `Outbox` queueing up to `capacity` notifications for the transport
+    /// to send, senders blocking while the queue is full.
+    pub fn bounded(capacity: usize) -> (Self, Receiver<Notification>) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let outbox = Self::new(move |notification| sender.send(notification).is_ok());
+        (outbox, receiver)
+    }
+
+    /// Sends given `notification` to the remote peer, returning `false` once it is gone.
+    pub fn send(&self, notification: Notification) -> bool {
+        (self.send)(notification)
+    }
+}
+
+impl fmt::Debug for Outbox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Outbox").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_extensions() {
+        let mut context = Context::new();
+        assert_eq!(context.get::<Peer>(), None);
+
+        context.insert(Peer("127.0.0.1:4000".to_owned()));
+        context.insert(Connection(1));
+        context.insert(Connection(2));
+        assert_eq!(context.get(), Some(&Peer("127.0.0.1:4000".to_owned())));
+        assert_eq!(context.get(), Some(&Connection(2)));
+
+        let clone = context.clone();
+        assert!(context.remove::<Peer>());
+        assert!(!context.contains::<Peer>());
+        assert!(clone.contains::<Peer>());
+    }
+
+    #[test]
+    fn context_deadline() {
+        let mut context = Context::new();
+        assert_eq!(context.remaining(), None);
+        assert!(!context.is_expired());
+
+        context.set_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(context.remaining().unwrap() > Duration::from_secs(30));
+        assert!(!context.is_expired());
+
+        context.set_deadline(Instant::now());
+        assert_eq!(context.remaining(), Some(Duration::from_secs(0)));
+        assert!(context.is_expired());
+    }
+}
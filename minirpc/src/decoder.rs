@@ -0,0 +1,389 @@
+//! MINI-RPC Streaming Decoder.
+//!
+//! Transports reading from a socket receive arbitrary chunks of bytes. A [`Decoder`]
+//! buffers them and yields each complete JSON document as soon as it is available,
+//! whether documents are separated by whitespace or simply concatenated.
+
+use crate::{Error, Limits, Request, Response};
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+use std::marker::PhantomData;
+
+/// Message decoded by a [`Decoder`].
+pub trait Message: DeserializeOwned {
+    /// Returns the error of a well-formed JSON document which is not a valid message.
+    fn invalid() -> Error;
+}
+
+impl<P> Message for Request<P>
+where
+    P: DeserializeOwned,
+{
+    fn invalid() -> Error {
+        Error::new_invalid_request()
+    }
+}
+
+impl<R, D> Message for Response<R, D>
+where
+    R: DeserializeOwned,
+    D: DeserializeOwned,
+{
+    fn invalid() -> Error {
+        Error::new_parse_error()
+    }
+}
+
+/// Push-based decoder of `Request` or `Response` values.
+///
+/// Invalid JSON yields a `ParseError` whose data holds the stream `offset` (in bytes) at
+/// which it was detected. Well-formed JSON which is not a valid `T` yields the error of
+/// [`Message::invalid`] (`InvalidRequest` for requests, `ParseError` for responses) with
+/// the reason as data. Either way, decoding resumes with the next document. Documents
+/// exceeding the decoder [`Limits`] (if any) also yield an `InvalidRequest`; the buffered
+/// input is discarded when an incomplete one does.
+#[derive(Debug)]
+pub struct Decoder<T> {
+    buffer: Vec<u8>,
+    start: usize,
+    consumed: usize,
+    scanner: Scanner,
+    limits: Option<Limits>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Decoder<T>
+where
+    T: Message,
+{
+    /// Creates a new, empty `Decoder`.
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            start: 0,
+            consumed: 0,
+            scanner: Scanner::default(),
+            limits: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a new, empty `Decoder` enforcing given `limits` on each document.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            limits: Some(limits),
+            ..Self::new()
+        }
+    }
+
+    /// Appends given `chunk` to the buffered input.
+    pub fn push(&mut self, chunk: &[u8]) {
+        // Decoded bytes are dropped once they make up most of the buffer, not to move
+        // the remaining ones after each document.
+        if self.start == self.buffer.len() || self.start > self.buffer.len() / 2 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Returns the number of buffered bytes which are not decoded yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+
+    /// Returns the buffered bytes which are not decoded yet.
+    fn pending(&self) -> &[u8] {
+        &self.buffer[self.start..]
+    }
+
+    /// Consumes the first `len` pending bytes.
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.consumed += len;
+        self.scanner = Scanner::default();
+    }
+
+    /// Discards the pending input.
+    fn reset(&mut self) {
+        let len = self.buffered();
+        self.consume(len);
+    }
+}
+
+impl<T> Default for Decoder<T>
+where
+    T: Message,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Iterator for Decoder<T>
+where
+    T: Message,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Find the boundary of the next document, resuming the scan where it stopped.
+        let end = match self.scanner.scan(&self.buffer[self.start..]) {
+            Some(end) => end,
+            None if self.scanner.is_blank() => {
+                self.reset();
+                return None;
+            }
+            None => {
+                // Do not buffer an incomplete document beyond the limits.
+                let limits = self.limits.as_ref()?;
+                if self.buffered() > limits.max_bytes {
+                    let result = limits.check(self.pending());
+                    self.reset();
+                    return result.err().map(Err);
+                }
+                return None;
+            }
+        };
+
+        if let Some(Err(err)) = self
+            .limits
+            .as_ref()
+            .map(|limits| limits.check(&self.pending()[..end]))
+        {
+            self.consume(end);
+            return Some(Err(err));
+        }
+
+        match serde_json::from_slice(&self.pending()[..end]) {
+            Ok(message) => {
+                self.consume(end);
+                Some(Ok(message))
+            }
+            Err(err) if err.classify() == Category::Data => {
+                self.consume(end);
+                Some(Err(T::invalid().with_data(err.to_string().into())))
+            }
+            Err(err) => {
+                let offset = self.consumed + position(self.pending(), err.line(), err.column());
+                // Resynchronize at the boundary following the malformed document.
+                self.consume(end);
+                Some(Err(
+                    Error::new_parse_error().with_data(json!({ "offset": offset }))
+                ))
+            }
+        }
+    }
+}
+
+/// Incremental scanner of the boundary of the next JSON document of a buffer.
+#[derive(Debug, Default)]
+struct Scanner {
+    /// Number of bytes of the buffer already scanned.
+    scanned: usize,
+
+    /// First byte of the document, once found.
+    start: Option<u8>,
+
+    /// Nesting depth within the document.
+    depth: usize,
+
+    /// Whether the scan is within a string.
+    string: bool,
+
+    /// Whether the previous byte of a string is an escape.
+    escape: bool,
+}
+
+impl Scanner {
+    /// Returns whether only whitespace was scanned.
+    fn is_blank(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// Scans the bytes of `buffer` added since the last scan, and returns the end of the
+    /// next document once known.
+    ///
+    /// A document ends with the byte closing its first byte, or before the whitespace or
+    /// delimiter following a scalar. Syntax errors are left to the parser: a byte which
+    /// cannot start a document ends it.
+    fn scan(&mut self, buffer: &[u8]) -> Option<usize> {
+        while self.scanned < buffer.len() {
+            let byte = buffer[self.scanned];
+            self.scanned += 1;
+
+            if self.string {
+                if self.escape {
+                    self.escape = false;
+                } else if byte == b'\\' {
+                    self.escape = true;
+                } else if byte == b'"' {
+                    self.string = false;
+                    if self.depth == 0 {
+                        return Some(self.scanned);
+                    }
+                }
+                continue;
+            }
+
+            match self.start {
+                None => match byte {
+                    b' ' | b'\t' | b'\n' | b'\r' => {}
+                    b'{' | b'[' => {
+                        self.start = Some(byte);
+                        self.depth = 1;
+                    }
+                    b'"' => {
+                        self.start = Some(byte);
+                        self.string = true;
+                    }
+                    b'-' | b'0'..=b'9' | b't' | b'f' | b'n' => self.start = Some(byte),
+                    _ => return Some(self.scanned),
+                },
+                Some(b'{') | Some(b'[') => match byte {
+                    b'"' => self.string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => {
+                        self.depth -= 1;
+                        if self.depth == 0 {
+                            return Some(self.scanned);
+                        }
+                    }
+                    _ => {}
+                },
+                Some(_) => match byte {
+                    b' ' | b'\t' | b'\n' | b'\r' | b'{' | b'[' | b'"' | b',' | b':' | b'}'
+                    | b']' => {
+                        self.scanned -= 1;
+                        return Some(self.scanned);
+                    }
+                    _ => {}
+                },
+            }
+        }
+        None
+    }
+}
+
+/// Converts a 1-based `line` and `column` into a byte index of `buffer`.
+fn position(buffer: &[u8], line: usize, column: usize) -> usize {
+    let start: usize = buffer
+        .split(|&byte| byte == b'\n')
+        .take(line.saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum();
+    (start + column.saturating_sub(1)).min(buffer.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Code;
+    use crate::{Call, Id, Method, Params, Request, RequestPayload, Response};
+    use serde_json::Value;
+
+    #[test]
+    fn decoder_partial_chunks() {
+        let input = br#"{"id":1,"method":"test_method","params":[1,2,3]}{"id":2,"method":"test_method","params":[]}"#;
+        let mut decoder = Decoder::<Request>::new();
+        let mut result = Vec::new();
+
+        for byte in input.iter() {
+            decoder.push(&[*byte]);
+            result.extend(&mut decoder);
+        }
+
+        let expected = vec![
+            Request::Single(RequestPayload::Call(Call {
+                id: Id::Number(1),
+                method: Method::String("test_method".to_owned()),
+                params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+                deadline: None,
+            })),
+            Request::Single(RequestPayload::Call(Call {
+                id: Id::Number(2),
+                method: Method::String("test_method".to_owned()),
+                params: Params::Array(vec![]),
+                deadline: None,
+            })),
+        ];
+        assert_eq!(
+            result.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn decoder_parse_error() {
+        let mut decoder = Decoder::<Response>::new();
+        decoder.push(b"{\"id\":1,\"result\":true}\n{\"id\":2,\n\"result\":tru}");
+
+        let result = decoder.next().unwrap();
+        assert!(result.is_ok());
+
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.code, Code::ParseError);
+        assert_eq!(result.data, Some(json!({ "offset": 44 })));
+        assert_eq!(decoder.buffered(), 0);
+        assert!(decoder.next().is_none());
+
+        // Documents following a malformed one are still decoded.
+        decoder.push(b"{\"id\":3,\"result\":nul} {\"id\":4,\"result\":null}");
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.data, Some(json!({ "offset": 65 })));
+        let result = decoder.next().unwrap();
+        assert!(result.is_ok());
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn decoder_invalid_request() {
+        let mut decoder = Decoder::<Request>::new();
+        decoder.push(br#"{"foo":"bar"} {"method":"test_method","params":[]}"#);
+
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.code, Code::InvalidRequest);
+
+        let result = decoder.next().unwrap();
+        assert!(result.is_ok());
+        assert!(decoder.next().is_none());
+
+        let mut decoder = Decoder::<Response>::new();
+        decoder.push(br#"{"foo":"bar"} {"id":1,"result":"}"}"#);
+
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.code, Code::ParseError);
+        let expected = "data did not match any variant of untagged enum Response";
+        assert_eq!(result.data, Some(Value::from(expected)));
+
+        let result = decoder.next().unwrap();
+        assert!(result.is_ok());
+        assert!(decoder.next().is_none());
+    }
+
+    #[test]
+    fn decoder_limits() {
+        let limits = Limits {
+            max_bytes: 64,
+            max_depth: 2,
+            ..Limits::default()
+        };
+        let mut decoder = Decoder::<Request>::with_limits(limits);
+
+        // Complete document exceeding the nesting depth.
+        decoder.push(br#"{"method":"test_method","params":[[1]]}"#);
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.code, Code::InvalidRequest);
+        assert_eq!(result.message, "Nesting exceeds 2 levels");
+
+        // Incomplete document exceeding the size.
+        decoder.push(br#"{"method":"test_method","params":["#);
+        assert!(decoder.next().is_none());
+        decoder.push(&[b'1', b','].repeat(16));
+        let result = decoder.next().unwrap().unwrap_err();
+        assert_eq!(result.code, Code::InvalidRequest);
+        assert_eq!(result.message, "Message exceeds 64 bytes");
+        assert_eq!(decoder.buffered(), 0);
+    }
+}
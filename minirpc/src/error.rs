@@ -2,10 +2,29 @@
 
 use serde::de::{Deserialize, Deserializer};
 use serde::ser::{Serialize, Serializer};
+use serde_json::Value;
 use std::fmt;
 
+/// Server error code of a request which could not be authenticated.
+pub const UNAUTHORIZED: i64 = -32001;
+
+/// Server error code of a request which is not authorized.
+pub const FORBIDDEN: i64 = -32002;
+
+/// Server error code of a request rejected by a rate or concurrency limit.
+pub const RATE_LIMITED: i64 = -32003;
+
+/// Error code of a call which was not answered in time.
+pub const TIMEOUT: i64 = -32004;
+
+/// Server error code of a call whose deadline passed before it completed.
+pub const DEADLINE_EXCEEDED: i64 = -32005;
+
+/// Server error code of a call cancelled by the caller.
+pub const CANCELLED: i64 = -32006;
+
 /// Error code.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Code {
     /// Invalid JSON was received by the server.
     /// An error occurred on the server while parsing the JSON text.
@@ -28,6 +47,18 @@ pub enum Code {
 }
 
 impl Code {
+    /// Returns the integer value of the code.
+    pub fn as_i64(&self) -> i64 {
+        match *self {
+            Code::ParseError => -32700,
+            Code::InvalidRequest => -32600,
+            Code::MethodNotFound => -32601,
+            Code::InvalidParams => -32602,
+            Code::InternalError => -32603,
+            Code::ServerError(code) => code,
+        }
+    }
+
     pub fn message(&self) -> &str {
         match *self {
             Code::ParseError => "Parse error",
@@ -61,29 +92,30 @@ impl Serialize for Code {
     where
         S: Serializer,
     {
-        serializer.serialize_i64(match *self {
-            Code::ParseError => -32700,
-            Code::InvalidRequest => -32600,
-            Code::MethodNotFound => -32601,
-            Code::InvalidParams => -32602,
-            Code::InternalError => -32603,
-            Code::ServerError(code) => code,
-        })
+        serializer.serialize_i64(self.as_i64())
     }
 }
 
 /// Error Object.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub struct Error {
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Error<D = Value> {
     pub code: Code,
     pub message: String,
+
+    /// Additional information about the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<D>,
 }
 
 impl Error {
     /// Creates a new `Error` for given `code`.
     pub fn new(code: Code) -> Self {
         let message = code.message().to_owned();
-        Self { code, message }
+        Self {
+            code,
+            message,
+            data: None,
+        }
     }
 
     /// Creates a new `ParseError`.
@@ -116,11 +148,53 @@ impl Error {
         Self {
             code: Code::ServerError(code),
             message: message.to_owned(),
+            data: None,
         }
     }
+
+    /// Creates a new `ServerError` for a request which could not be authenticated.
+    pub fn new_unauthorized() -> Self {
+        Self::new_server_error(UNAUTHORIZED, "Unauthorized")
+    }
+
+    /// Creates a new `ServerError` for a request which is not authorized.
+    pub fn new_forbidden() -> Self {
+        Self::new_server_error(FORBIDDEN, "Forbidden")
+    }
+
+    /// Creates a new `ServerError` for a request rejected by a rate or concurrency limit.
+    pub fn new_rate_limited() -> Self {
+        Self::new_server_error(RATE_LIMITED, "Rate limited")
+    }
+
+    /// Creates a new `ServerError` for a call which was not answered in time.
+    pub fn new_timeout() -> Self {
+        Self::new_server_error(TIMEOUT, "Timeout")
+    }
+
+    /// Creates a new `ServerError` for a call whose deadline passed before it completed.
+    pub fn new_deadline_exceeded() -> Self {
+        Self::new_server_error(DEADLINE_EXCEEDED, "Deadline exceeded")
+    }
+
+    /// Creates a new `ServerError` for a call cancelled by the caller.
+    pub fn new_cancelled() -> Self {
+        Self::new_server_error(CANCELLED, "Cancelled")
+    }
 }
 
-impl fmt::Display for Error {
+impl<D> Error<D> {
+    /// Attaches given `data` to the error.
+    pub fn with_data<T>(self, data: T) -> Error<T> {
+        Error {
+            code: self.code,
+            message: self.message,
+            data: Some(data),
+        }
+    }
+}
+
+impl<D> fmt::Display for Error<D> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.message)
     }
@@ -173,6 +247,7 @@ mod tests {
         let expected = Error {
             code: Code::ParseError,
             message: "Parse error".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -184,6 +259,7 @@ mod tests {
         let expected = Error {
             code: Code::ParseError,
             message: "Parse error".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -195,6 +271,7 @@ mod tests {
         let expected = Error {
             code: Code::InvalidRequest,
             message: "Invalid request".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -206,6 +283,7 @@ mod tests {
         let expected = Error {
             code: Code::MethodNotFound,
             message: "Method not found".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -217,6 +295,7 @@ mod tests {
         let expected = Error {
             code: Code::InvalidParams,
             message: "Invalid params".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -228,6 +307,7 @@ mod tests {
         let expected = Error {
             code: Code::InternalError,
             message: "Internal error".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
@@ -239,8 +319,127 @@ mod tests {
         let expected = Error {
             code: Code::ServerError(-32000),
             message: "Test error".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_new_unauthorized() {
+        let result = Error::new_unauthorized();
+        let expected = Error {
+            code: Code::ServerError(-32001),
+            message: "Unauthorized".to_owned(),
+            data: None,
         };
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn error_new_forbidden() {
+        let result = Error::new_forbidden();
+        let expected = Error {
+            code: Code::ServerError(-32002),
+            message: "Forbidden".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_new_rate_limited() {
+        let result = Error::new_rate_limited();
+        let expected = Error {
+            code: Code::ServerError(-32003),
+            message: "Rate limited".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_new_timeout() {
+        let result = Error::new_timeout();
+        let expected = Error {
+            code: Code::ServerError(-32004),
+            message: "Timeout".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_new_deadline_exceeded() {
+        let result = Error::new_deadline_exceeded();
+        let expected = Error {
+            code: Code::ServerError(-32005),
+            message: "Deadline exceeded".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_new_cancelled() {
+        let result = Error::new_cancelled();
+        let expected = Error {
+            code: Code::ServerError(-32006),
+            message: "Cancelled".to_owned(),
+            data: None,
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn error_with_data() {
+        let input = Error::new_invalid_params().with_data(Value::from("Missing field"));
+        let expected = r#"{"code":-32602,"message":"Invalid params","data":"Missing field"}"#;
+
+        let result = serde_json::to_string(&input).unwrap();
+        assert_eq!(result, expected);
+
+        let result: Error = serde_json::from_str(expected).unwrap();
+        assert_eq!(result, input);
+    }
+
+    #[cfg(feature = "raw_value")]
+    #[test]
+    fn error_raw_value_passthrough() {
+        use serde_json::value::RawValue;
+
+        let input = r#"{"code":-32602,"message":"Invalid params","data":[ "a",  "b" ]}"#;
+
+        let result: Error<Box<RawValue>> = serde_json::from_str(input).unwrap();
+        assert_eq!(result.code, Code::InvalidParams);
+        assert_eq!(result.data.as_ref().unwrap().get(), r#"[ "a",  "b" ]"#);
+
+        let result = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, input);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn code_msgpack_round_trip() {
+        let inputs = vec![
+            Code::ParseError,
+            Code::InvalidRequest,
+            Code::MethodNotFound,
+            Code::InvalidParams,
+            Code::InternalError,
+            Code::ServerError(-32000),
+        ];
+
+        for input in inputs {
+            let bytes = crate::msgpack::to_vec(&input).unwrap();
+            let result: Code = crate::msgpack::from_slice(&bytes).unwrap();
+            assert_eq!(result, input);
+        }
+    }
 }
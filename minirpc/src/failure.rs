@@ -1,12 +1,13 @@
 //! MINI-RPC Response Failure.
 
 use crate::{Error, Id};
+use serde_json::Value;
 
 /// Response failure.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub struct Failure {
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Failure<D = Value> {
     /// Error.
-    pub error: Error,
+    pub error: Error<D>,
 
     /// Correlation id.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1,13 +1,23 @@
 //! MINI-RPC Id.
 
+use std::fmt;
+
 /// Request id.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Id {
     /// Numeric id.
     Number(u64),
 }
 
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Id::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,202 @@
+//! MINI-RPC Interceptors.
+//!
+//! Interceptors add cross-cutting behaviour (logging, auth, metrics, param rewriting)
+//! around method invocation, on both the serving and the calling side. A [`Chain`] runs
+//! `before_*` hooks in order and `after_*` hooks in reverse order, so each interceptor
+//! wraps the ones registered after it.
+
+use crate::{Call, Context, Error, Failure, Notification, ResponsePayload, Success};
+use std::sync::Arc;
+
+/// Hooks around method invocation.
+///
+/// The `Context` given to the `before_*` hooks is the one later given to the handler
+/// and to the `after_*` hooks, so interceptors can populate it or keep state in it.
+pub trait Interceptor: Send + Sync {
+    /// Called before a call is dispatched. Returning an error short-circuits the call,
+    /// which is answered with a failure.
+    fn before_call(&self, _context: &mut Context, _call: &mut Call) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called before a notification is dispatched. Returning an error drops it.
+    fn before_notification(
+        &self,
+        _context: &mut Context,
+        _notification: &mut Notification,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after a call succeeded.
+    fn after_success(&self, _context: &Context, _success: &mut Success) {}
+
+    /// Called after a call failed.
+    fn after_failure(&self, _context: &Context, _failure: &mut Failure) {}
+}
+
+/// Ordered interceptors.
+#[derive(Clone, Default)]
+pub struct Chain {
+    interceptors: Vec<Arc<dyn Interceptor>>,
+}
+
+impl Chain {
+    /// Creates a new, empty `Chain`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends given `interceptor` to the chain.
+    pub fn push<I>(&mut self, interceptor: I)
+    where
+        I: Interceptor + 'static,
+    {
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    /// Returns `true` if the chain contains no interceptor.
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.is_empty()
+    }
+
+    /// Runs the `before_call` hooks in order.
+    ///
+    /// When a hook short-circuits, the interceptors which already ran see the resulting
+    /// failure through their `after_failure` hook, which is then returned.
+    pub fn before_call(&self, context: &mut Context, call: &mut Call) -> Result<(), Failure> {
+        for (index, interceptor) in self.interceptors.iter().enumerate() {
+            if let Err(error) = interceptor.before_call(context, call) {
+                let mut failure = Failure {
+                    error,
+                    id: Some(call.id.clone()),
+                };
+                for interceptor in self.interceptors[..index].iter().rev() {
+                    interceptor.after_failure(context, &mut failure);
+                }
+                return Err(failure);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the `before_notification` hooks in order, stopping at the first error.
+    pub fn before_notification(
+        &self,
+        context: &mut Context,
+        notification: &mut Notification,
+    ) -> Result<(), Error> {
+        self.interceptors
+            .iter()
+            .try_for_each(|interceptor| interceptor.before_notification(context, notification))
+    }
+
+    /// Runs the `after_success` or `after_failure` hooks in reverse order.
+    pub fn after(&self, context: &Context, payload: &mut ResponsePayload) {
+        for interceptor in self.interceptors.iter().rev() {
+            match payload {
+                ResponsePayload::Success(success) => interceptor.after_success(context, success),
+                ResponsePayload::Failure(failure) => interceptor.after_failure(context, failure),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Id, Method, Params};
+    use serde_json::Value;
+    use std::sync::Mutex;
+
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        deny: bool,
+    }
+
+    impl Interceptor for Recorder {
+        fn before_call(&self, _context: &mut Context, _call: &mut Call) -> Result<(), Error> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {}", self.name));
+            if self.deny {
+                return Err(Error::new_server_error(-32000, "Denied"));
+            }
+            Ok(())
+        }
+
+        fn after_success(&self, _context: &Context, _success: &mut Success) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("success {}", self.name));
+        }
+
+        fn after_failure(&self, _context: &Context, _failure: &mut Failure) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("failure {}", self.name));
+        }
+    }
+
+    fn call() -> Call {
+        Call {
+            id: Id::Number(1),
+            method: Method::String("test_method".to_owned()),
+            params: Params::Array(vec![]),
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn chain_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut chain = Chain::new();
+        for name in &["a", "b"] {
+            chain.push(Recorder {
+                name,
+                log: log.clone(),
+                deny: false,
+            });
+        }
+
+        let mut context = Context::new();
+        chain.before_call(&mut context, &mut call()).unwrap();
+        chain.after(
+            &context,
+            &mut ResponsePayload::Success(Success {
+                id: Id::Number(1),
+                result: Value::Null,
+            }),
+        );
+
+        let expected = vec!["before a", "before b", "success b", "success a"];
+        assert_eq!(*log.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn chain_short_circuit() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut chain = Chain::new();
+        for (name, deny) in &[("a", false), ("b", true), ("c", false)] {
+            chain.push(Recorder {
+                name,
+                log: log.clone(),
+                deny: *deny,
+            });
+        }
+
+        let result = chain
+            .before_call(&mut Context::new(), &mut call())
+            .unwrap_err();
+        assert_eq!(result.error.message, "Denied");
+        assert_eq!(result.id, Some(Id::Number(1)));
+
+        let expected = vec!["before a", "before b", "failure a"];
+        assert_eq!(*log.lock().unwrap(), expected);
+    }
+}
@@ -0,0 +1,438 @@
+//! MINI-RPC Introspection.
+//!
+//! A server with introspection enabled (see `Server::set_introspection`) answers
+//! reserved methods about itself:
+//!
+//! - [`METHODS`] returns the names of the available methods, in order.
+//! - [`DESCRIBE`] returns the [`Description`] of the method named by its `method` param.
+//! - [`VERSION`] returns the [`Info`] of the server.
+//! - [`DISCOVER`] returns the OpenRPC document of the server (see `openrpc`).
+//!
+//! Descriptions are set when registering methods, with `Methods::describe`. The params
+//! and result they describe are JSON Schemas.
+
+use crate::methods::Methods;
+use crate::openrpc;
+use crate::schema::{self, Violation};
+use crate::service::{to_value, Args};
+use crate::{Error, Method, Params};
+use serde_json::Value;
+
+/// Method listing the available methods.
+pub const METHODS: &str = "rpc.methods";
+
+/// Method describing a method.
+pub const DESCRIBE: &str = "rpc.describe";
+
+/// Method returning the server info.
+pub const VERSION: &str = "rpc.version";
+
+/// Method returning the OpenRPC document of the server.
+pub const DISCOVER: &str = "rpc.discover";
+
+/// Introspection methods, in order.
+const RESERVED: [&str; 4] = [DESCRIBE, DISCOVER, METHODS, VERSION];
+
+/// Server info.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Info {
+    /// Server name.
+    pub name: String,
+
+    /// Server version.
+    pub version: String,
+}
+
+impl Info {
+    /// Creates a new `Info` of given server `name` and `version`.
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: version.to_owned(),
+        }
+    }
+}
+
+/// Description of a method.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Description {
+    /// Short summary of what the method does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+
+    /// Params of the method, in order.
+    #[serde(default)]
+    pub params: Vec<Param>,
+
+    /// Whether params are given by position, by name, or either.
+    #[serde(
+        default,
+        rename = "paramStructure",
+        skip_serializing_if = "ParamStructure::is_either"
+    )]
+    pub param_structure: ParamStructure,
+
+    /// JSON Schema of the result, if the method is called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+
+    /// Errors the method can fail with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Error>,
+}
+
+impl Description {
+    /// Creates a new `Description` without any param nor result.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the summary of the method.
+    pub fn with_summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+
+    /// Appends a required param `name` of given JSON `schema`.
+    pub fn with_param(mut self, name: &str, schema: Value) -> Self {
+        self.params.push(Param::new(name, schema, true));
+        self
+    }
+
+    /// Appends an optional param `name` of given JSON `schema`.
+    pub fn with_optional_param(mut self, name: &str, schema: Value) -> Self {
+        self.params.push(Param::new(name, schema, false));
+        self
+    }
+
+    /// Sets whether params are given by position, by name, or either.
+    pub fn with_param_structure(mut self, param_structure: ParamStructure) -> Self {
+        self.param_structure = param_structure;
+        self
+    }
+
+    /// Sets the JSON `schema` of the result.
+    pub fn with_result(mut self, schema: Value) -> Self {
+        self.result = Some(schema);
+        self
+    }
+
+    /// Appends an `error` the method can fail with.
+    pub fn with_error(mut self, error: Error) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Checks given `params` against the described ones.
+    ///
+    /// Fails with `Error::new_invalid_params()` whose data lists every [`Violation`],
+    /// located by its JSON Pointer in the params.
+    pub fn validate(&self, params: &Params) -> Result<(), Error> {
+        let mut violations = Vec::new();
+        match params {
+            Params::Array(values) => {
+                if self.param_structure == ParamStructure::ByName {
+                    violations.push(Violation::new("", "expected params by name"));
+                }
+                for (index, param) in self.params.iter().enumerate() {
+                    let path = schema::pointer("", &index.to_string());
+                    param.validate(values.get(index), &path, &mut violations);
+                }
+                for index in self.params.len()..values.len() {
+                    let path = schema::pointer("", &index.to_string());
+                    violations.push(Violation::new(&path, "unexpected param"));
+                }
+            }
+            Params::Object(map) => {
+                if self.param_structure == ParamStructure::ByPosition {
+                    violations.push(Violation::new("", "expected params by position"));
+                }
+                for param in &self.params {
+                    let path = schema::pointer("", &param.name);
+                    param.validate(map.get(&param.name), &path, &mut violations);
+                }
+                for name in map.keys() {
+                    if self.params.iter().all(|param| &param.name != name) {
+                        let path = schema::pointer("", name);
+                        violations.push(Violation::new(&path, "unexpected param"));
+                    }
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new_invalid_params().with_data(json!(violations)))
+        }
+    }
+}
+
+/// Structure of the params of a method.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParamStructure {
+    /// Params are given by position, as `Params::Array`.
+    ByPosition,
+
+    /// Params are given by name, as `Params::Object`.
+    ByName,
+
+    /// Params are given either way.
+    #[default]
+    Either,
+}
+
+impl ParamStructure {
+    /// Returns whether params can be given either way.
+    pub fn is_either(&self) -> bool {
+        *self == ParamStructure::Either
+    }
+}
+
+/// Description of a method param.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Param {
+    /// Param name, when given by name.
+    pub name: String,
+
+    /// JSON Schema of the param.
+    pub schema: Value,
+
+    /// Whether the param must be given.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl Param {
+    /// Creates a new `Param` named `name` of given JSON `schema`.
+    pub fn new(name: &str, schema: Value, required: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            schema,
+            required,
+        }
+    }
+
+    /// Appends the violations of this param by given `value`, at `path`.
+    fn validate(&self, value: Option<&Value>, path: &str, violations: &mut Vec<Violation>) {
+        match value {
+            Some(value) => {
+                violations.extend(schema::validate(&self.schema, value).into_iter().map(
+                    |violation| Violation {
+                        path: format!("{}{}", path, violation.path),
+                        ..violation
+                    },
+                ))
+            }
+            None if self.required => violations.push(Violation::new(path, "missing param")),
+            None => {}
+        }
+    }
+}
+
+/// Returns whether `method` is an introspection method.
+pub fn is_reserved(method: &str) -> bool {
+    RESERVED.contains(&method)
+}
+
+/// Returns the description of given introspection `method`.
+pub fn reserved(method: &str) -> Option<Description> {
+    let description = match method {
+        METHODS => Description::new()
+            .with_summary("Lists the available methods.")
+            .with_result(json!({"type": "array", "items": {"type": "string"}})),
+        DESCRIBE => Description::new()
+            .with_summary("Describes a method.")
+            .with_param("method", json!({"type": "string"}))
+            .with_result(json!({"type": "object"})),
+        VERSION => Description::new()
+            .with_summary("Returns the server name and version.")
+            .with_result(json!({"type": "object"})),
+        DISCOVER => Description::new()
+            .with_summary("Returns the OpenRPC document of the server.")
+            .with_result(json!({"type": "object"})),
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// Answers the call of introspection `method` with given `params`, about `methods` of
+/// the server of given `info`.
+pub(crate) fn handle(
+    info: &Info,
+    methods: &Methods,
+    method: &str,
+    params: Params,
+) -> Result<Value, Error> {
+    match method {
+        METHODS => {
+            Args::new(params).finish()?;
+            let mut names = methods.names();
+            names.extend(RESERVED.iter().map(|&name| Method::String(name.to_owned())));
+            names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            to_value(&names)
+        }
+        DESCRIBE => {
+            let mut args = Args::new(params);
+            let method: String = args.next("method")?;
+            args.finish()?;
+            to_value(&describe(methods, &method)?)
+        }
+        VERSION => {
+            Args::new(params).finish()?;
+            to_value(info)
+        }
+        DISCOVER => {
+            Args::new(params).finish()?;
+            Ok(openrpc::document(info, methods))
+        }
+        _ => Err(Error::new_method_not_found()),
+    }
+}
+
+/// Returns the description of `method`, which must be available.
+fn describe(methods: &Methods, method: &str) -> Result<Description, Error> {
+    if let Some(description) = reserved(method) {
+        return Ok(description);
+    }
+    if methods.get(method).is_none() {
+        return Err(Error::new_invalid_params());
+    }
+    Ok(methods.description(method).cloned().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Server};
+
+    fn server() -> Server {
+        let mut server = Server::new();
+        server
+            .register("sum", |_: &Context, _params| Ok(Value::from(0)))
+            .unwrap();
+        server
+            .register("ping", |_: &Context, _params| Ok(Value::Null))
+            .unwrap();
+        server
+            .describe(
+                "sum",
+                Description::new()
+                    .with_summary("Sums numbers.")
+                    .with_param("a", json!({"type": "integer"}))
+                    .with_optional_param("b", json!({"type": "integer"}))
+                    .with_result(json!({"type": "integer"})),
+            )
+            .unwrap();
+        server
+    }
+
+    fn handle(server: &Server, input: &str) -> String {
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        serde_json::to_string(&result).unwrap()
+    }
+
+    #[test]
+    fn introspect_disabled() {
+        let server = server();
+
+        let input = r#"{"id":1,"method":"rpc.methods","params":[]}"#;
+        let expected = r#"{"error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+        assert_eq!(handle(&server, input), expected);
+    }
+
+    #[test]
+    fn introspect_methods() {
+        let mut server = server();
+        server.set_introspection(Info::new("test", "1.0.0"));
+
+        let input = r#"{"id":1,"method":"rpc.methods","params":[]}"#;
+        let expected = concat!(
+            r#"{"id":1,"result":"#,
+            r#"["ping","rpc.describe","rpc.discover","rpc.methods","rpc.version","sum"]}"#,
+        );
+        assert_eq!(handle(&server, input), expected);
+
+        let input = r#"{"id":1,"method":"rpc.version","params":[]}"#;
+        let expected = r#"{"id":1,"result":{"name":"test","version":"1.0.0"}}"#;
+        assert_eq!(handle(&server, input), expected);
+
+        let input = r#"{"id":1,"method":"rpc.version","params":[1]}"#;
+        let expected = r#"{"error":{"code":-32602,"message":"Invalid params"},"id":1}"#;
+        assert_eq!(handle(&server, input), expected);
+    }
+
+    #[test]
+    fn introspect_describe() {
+        let mut server = server();
+        server.set_introspection(Info::new("test", "1.0.0"));
+
+        let input = r#"{"id":1,"method":"rpc.describe","params":["sum"]}"#;
+        let expected = concat!(
+            r#"{"id":1,"result":{"params":["#,
+            r#"{"name":"a","required":true,"schema":{"type":"integer"}},"#,
+            r#"{"name":"b","required":false,"schema":{"type":"integer"}}],"#,
+            r#""result":{"type":"integer"},"summary":"Sums numbers."}}"#,
+        );
+        assert_eq!(handle(&server, input), expected);
+
+        let input = r#"{"id":1,"method":"rpc.describe","params":{"method":"ping"}}"#;
+        let expected = r#"{"id":1,"result":{"params":[]}}"#;
+        assert_eq!(handle(&server, input), expected);
+
+        let input = r#"{"id":1,"method":"rpc.describe","params":["rpc.version"]}"#;
+        let result: Value = serde_json::from_str(&handle(&server, input)).unwrap();
+        assert_eq!(
+            result["result"]["summary"],
+            "Returns the server name and version."
+        );
+
+        let input = r#"{"id":1,"method":"rpc.describe","params":["unknown"]}"#;
+        let expected = r#"{"error":{"code":-32602,"message":"Invalid params"},"id":1}"#;
+        assert_eq!(handle(&server, input), expected);
+    }
+
+    #[test]
+    fn introspect_validate() {
+        let mut server = server();
+
+        // Descriptions are only enforced with validation enabled.
+        let input = r#"{"id":1,"method":"sum","params":["a"]}"#;
+        let expected = r#"{"id":1,"result":0}"#;
+        assert_eq!(handle(&server, input), expected);
+
+        server.set_validation(true);
+        for input in &[
+            r#"{"id":1,"method":"sum","params":[1]}"#,
+            r#"{"id":1,"method":"sum","params":{"a":1,"b":2}}"#,
+            r#"{"id":1,"method":"ping","params":["any"]}"#,
+        ] {
+            assert!(handle(&server, input).contains(r#""result""#));
+        }
+
+        let input = r#"{"id":1,"method":"sum","params":["a",2,3]}"#;
+        let expected = concat!(
+            r#"{"error":{"code":-32602,"message":"Invalid params","data":["#,
+            r#"{"message":"expected integer","path":"/0"},"#,
+            r#"{"message":"unexpected param","path":"/2"}]},"id":1}"#,
+        );
+        assert_eq!(handle(&server, input), expected);
+
+        let input = r#"{"id":1,"method":"sum","params":{"b":1.5,"c":0}}"#;
+        let expected = concat!(
+            r#"{"error":{"code":-32602,"message":"Invalid params","data":["#,
+            r#"{"message":"missing param","path":"/a"},"#,
+            r#"{"message":"expected integer","path":"/b"},"#,
+            r#"{"message":"unexpected param","path":"/c"}]},"id":1}"#,
+        );
+        assert_eq!(handle(&server, input), expected);
+
+        // Methods which are not registered cannot be described, and are not found.
+        let description = Description::new().with_param("a", json!({}));
+        assert!(server.describe("mul", description).is_err());
+        let input = r#"{"id":1,"method":"mul","params":[]}"#;
+        let expected = r#"{"error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+        assert_eq!(handle(&server, input), expected);
+    }
+}
@@ -2,31 +2,77 @@
 //! Inspired by the [JSON-RPC 2.0 Specification](https://www.jsonrpc.org/specification),
 //! this implementation written in Rust selected its essential parts in order to keep
 //! as minimal as possible the communication between processes.
+//!
+//! Params, results and error data default to JSON values, but the message types are
+//! generic over them: they can hold any serializable type, e.g. `Box<RawValue>` (with
+//! the `raw_value` feature) to forward them byte-for-byte.
 
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
 
+pub mod auth;
 pub mod call;
+pub mod cancel;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod chunk;
+pub mod client;
+pub mod codec;
+pub mod context;
+pub mod decoder;
 pub mod error;
 pub mod failure;
 pub mod id;
+pub mod interceptor;
+pub mod introspect;
+pub mod limits;
 pub mod method;
+pub mod methods;
+pub mod metrics;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 pub mod notification;
+pub mod openrpc;
 pub mod params;
+pub mod policy;
+pub mod progress;
 pub mod request;
 pub mod response;
+pub mod schema;
+pub mod server;
+pub mod service;
+pub mod shape;
+pub mod subscription;
 pub mod success;
+pub mod throttle;
 
 pub use self::call::Call;
+pub use self::client::{Client, Reply};
+pub use self::codec::{Codec, Format};
+pub use self::context::Context;
+pub use self::decoder::Decoder;
 pub use self::error::Error;
 pub use self::failure::Failure;
 pub use self::id::Id;
+pub use self::interceptor::Interceptor;
+pub use self::limits::Limits;
 pub use self::method::Method;
+pub use self::methods::Methods;
 pub use self::notification::Notification;
 pub use self::params::Params;
 pub use self::request::Payload as RequestPayload;
 pub use self::request::Request;
 pub use self::response::Payload as ResponsePayload;
 pub use self::response::Response;
+pub use self::server::{Handler, Server};
+pub use self::service::Service;
 pub use self::success::Success;
 pub use serde_json::{Map, Value};
+
+#[cfg(feature = "raw_value")]
+pub use serde_json::value::RawValue;
+
+#[cfg(feature = "derive")]
+pub use minirpc_derive::service;
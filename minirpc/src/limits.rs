@@ -0,0 +1,206 @@
+//! MINI-RPC Message Limits.
+//!
+//! Limits are checked by scanning the raw bytes before anything is decoded, so an
+//! oversized batch or a deeply nested `Params` is rejected without allocating it.
+//!
+//! Limits are opt-in and applied where bytes are decoded: by the transport, with
+//! [`Limits::parse_request`] or a `Decoder` created with `Decoder::with_limits`. The
+//! `Server` only handles decoded requests, so it does not apply them.
+
+use crate::error::Code;
+use crate::{Error, Request};
+use serde::de::DeserializeOwned;
+
+/// Message limits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Limits {
+    /// Maximum size of a message, in bytes.
+    pub max_bytes: usize,
+
+    /// Maximum number of payloads in a batch.
+    pub max_batch_len: usize,
+
+    /// Maximum nesting depth of arrays and objects.
+    pub max_depth: usize,
+
+    /// Maximum length of a string (including object keys), in bytes.
+    pub max_string_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1024 * 1024,
+            max_batch_len: 1024,
+            max_depth: 64,
+            max_string_len: 64 * 1024,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks given JSON `bytes` against the limits.
+    pub fn check(&self, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.len() > self.max_bytes {
+            return Err(violation(format!(
+                "Message exceeds {} bytes",
+                self.max_bytes
+            )));
+        }
+
+        let mut depth = 0;
+        let mut batch = false;
+        let mut batch_len = 0;
+        let mut string_len = None;
+        let mut escaped = false;
+
+        for &byte in bytes {
+            if let Some(len) = string_len {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    string_len = None;
+                    continue;
+                }
+
+                if len >= self.max_string_len {
+                    return Err(violation(format!(
+                        "String exceeds {} bytes",
+                        self.max_string_len
+                    )));
+                }
+                string_len = Some(len + 1);
+                continue;
+            }
+
+            if batch && depth == 1 && !byte.is_ascii_whitespace() && byte != b']' {
+                // First byte of the batch, or a separator between payloads.
+                if batch_len == 0 || byte == b',' {
+                    batch_len += 1;
+                    if batch_len > self.max_batch_len {
+                        return Err(violation(format!(
+                            "Batch exceeds {} payloads",
+                            self.max_batch_len
+                        )));
+                    }
+                }
+            }
+
+            match byte {
+                b'"' => string_len = Some(0),
+                b'[' | b'{' => {
+                    if depth == 0 && byte == b'[' {
+                        batch = true;
+                    }
+                    depth += 1;
+                    if depth > self.max_depth {
+                        return Err(violation(format!(
+                            "Nesting exceeds {} levels",
+                            self.max_depth
+                        )));
+                    }
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks given JSON `bytes` against the limits, then parses them as a `Request`.
+    pub fn parse_request<P>(&self, bytes: &[u8]) -> Result<Request<P>, Error>
+    where
+        P: DeserializeOwned,
+    {
+        self.check(bytes)?;
+        serde_json::from_slice(bytes).map_err(|err| {
+            if err.is_syntax() || err.is_eof() {
+                Error::new_parse_error()
+            } else {
+                Error::new_invalid_request()
+            }
+        })
+    }
+}
+
+/// Creates an `InvalidRequest` error explaining which limit is exceeded.
+fn violation(message: String) -> Error {
+    Error {
+        code: Code::InvalidRequest,
+        message,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_bytes: 100,
+            max_batch_len: 2,
+            max_depth: 3,
+            max_string_len: 12,
+        }
+    }
+
+    #[test]
+    fn limits_check() {
+        let inputs = vec![
+            r#"{"id":1,"method":"test_method","params":[1,[2,3]]}"#,
+            r#"[{"method":"test_method","params":["[[[["]}, {"method":"test_method","params":[]}]"#,
+            r#"{"method":"test_method","params":["\"escaped\""]}"#,
+            r#"[]"#,
+        ];
+
+        for input in inputs {
+            assert_eq!(limits().check(input.as_bytes()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn limits_check_violation() {
+        let inputs = vec![
+            (
+                r#"{"method":"test_method","params":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1]}"#,
+                "Message exceeds 100 bytes",
+            ),
+            (
+                r#"[{"method":"a","params":[]},{"method":"b","params":[]},{"method":"c","params":[]}]"#,
+                "Batch exceeds 2 payloads",
+            ),
+            (
+                r#"{"method":"test_method","params":[[[1]]]}"#,
+                "Nesting exceeds 3 levels",
+            ),
+            (
+                r#"{"method":"test_method_too_long","params":[]}"#,
+                "String exceeds 12 bytes",
+            ),
+        ];
+
+        for (input, message) in inputs {
+            let result = limits().check(input.as_bytes()).unwrap_err();
+            assert_eq!(result.code, Code::InvalidRequest);
+            assert_eq!(result.message, message);
+        }
+    }
+
+    #[test]
+    fn limits_parse_request() {
+        let result: Request = limits()
+            .parse_request(br#"{"method":"test_method","params":[]}"#)
+            .unwrap();
+        assert!(matches!(result, Request::Single(_)));
+
+        let result = limits().parse_request::<crate::Params>(br#"{"method":"#);
+        assert_eq!(result.unwrap_err().code, Code::ParseError);
+
+        let result = limits().parse_request::<crate::Params>(br#"{"foo":"bar"}"#);
+        assert_eq!(result.unwrap_err().code, Code::InvalidRequest);
+    }
+}
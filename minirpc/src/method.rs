@@ -3,13 +3,22 @@
 use std::fmt;
 
 /// Request method.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Method {
     /// String method.
     String(String),
 }
 
+impl Method {
+    /// Returns the method name.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::String(string) => string,
+        }
+    }
+}
+
 impl fmt::Display for Method {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
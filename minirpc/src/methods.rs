@@ -0,0 +1,297 @@
+//! MINI-RPC Method Tables.
+//!
+//! [`Methods`] maps method names to handlers, and composes: a table can be mounted
+//! under a prefix in another one, so that `get` and `list` of a `user` table become
+//! `user.get` and `user.list`. The separator between a prefix and a name is `.` unless
+//! set otherwise on the table mounting.
+//!
+//! Registered methods can be given a [`Description`] of their params and result, which
+//! follows them when their table is mounted.
+//!
+//! Registering a name twice, or a name reserved by the protocol (the introspection
+//! methods and `$/cancel`), is a [`Collision`], detected when the method is registered
+//! or its table mounted, and leaving the table unchanged.
+
+use crate::cancel::CANCEL;
+use crate::introspect::{self, Description};
+use crate::server::Handler;
+use crate::service::Service;
+use crate::{Context, Method};
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+/// Default separator between a prefix and a method name.
+pub const DEFAULT_SEPARATOR: &str = ".";
+
+/// Registration of a method name which is already registered, or reserved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Collision(pub String);
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Method already registered or reserved: {}", self.0)
+    }
+}
+
+impl StdError for Collision {}
+
+/// Reference to a method name which is not registered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unregistered(pub String);
+
+impl fmt::Display for Unregistered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Method not registered: {}", self.0)
+    }
+}
+
+impl StdError for Unregistered {}
+
+/// Table of method handlers.
+pub struct Methods {
+    separator: String,
+    handlers: HashMap<String, Box<dyn Handler>>,
+    descriptions: HashMap<String, Description>,
+}
+
+impl Methods {
+    /// Creates a new `Methods` without any method.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the separator between the prefix of a mounted table and its names.
+    pub fn set_separator(&mut self, separator: &str) {
+        self.separator = separator.to_owned();
+    }
+
+    /// Registers given `handler` for `method`.
+    pub fn register<H>(&mut self, method: &str, handler: H) -> Result<(), Collision>
+    where
+        H: Handler + 'static,
+    {
+        self.check(Some(method.to_owned()))?;
+        self.insert(method, handler);
+        Ok(())
+    }
+
+    /// Registers the methods of given `service`.
+    pub fn serve<S>(&mut self, service: S) -> Result<(), Collision>
+    where
+        S: Service + 'static,
+    {
+        let names = service.methods();
+        self.check(names.iter().map(|&name| name.to_owned()))?;
+
+        let service = Arc::new(service);
+        for &method in names {
+            let service = service.clone();
+            self.insert(method, move |context: &Context, params| {
+                service.call(context, method, params)
+            });
+        }
+        Ok(())
+    }
+
+    /// Registers the methods of given `methods` under `prefix`.
+    pub fn mount(&mut self, prefix: &str, methods: Methods) -> Result<(), Collision> {
+        let handlers: Vec<_> = methods
+            .handlers
+            .into_iter()
+            .map(|(name, handler)| (format!("{}{}{}", prefix, self.separator, name), handler))
+            .collect();
+        self.check(handlers.iter().map(|(name, _)| name.clone()))?;
+
+        self.handlers.extend(handlers);
+        for (name, description) in methods.descriptions {
+            let name = format!("{}{}{}", prefix, self.separator, name);
+            self.descriptions.entry(name).or_insert(description);
+        }
+        Ok(())
+    }
+
+    /// Sets the description of registered `method`.
+    pub fn describe(&mut self, method: &str, description: Description) -> Result<(), Unregistered> {
+        if !self.handlers.contains_key(method) {
+            return Err(Unregistered(method.to_owned()));
+        }
+        self.descriptions.insert(method.to_owned(), description);
+        Ok(())
+    }
+
+    /// Returns the description of `method`, if any.
+    pub fn description(&self, method: &str) -> Option<&Description> {
+        self.descriptions.get(method)
+    }
+
+    /// Returns the handler of `method`, if any.
+    pub fn get(&self, method: &str) -> Option<&dyn Handler> {
+        self.handlers.get(method).map(|handler| &**handler)
+    }
+
+    /// Returns the fully-qualified names of the methods, in order.
+    pub fn names(&self) -> Vec<Method> {
+        let mut names: Vec<_> = self.handlers.keys().cloned().collect();
+        names.sort();
+        names.into_iter().map(Method::String).collect()
+    }
+
+    /// Returns the number of methods.
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns whether there is no method.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Registers given `handler` for `method`, replacing any previous one.
+    fn insert<H>(&mut self, method: &str, handler: H)
+    where
+        H: Handler + 'static,
+    {
+        self.handlers.insert(method.to_owned(), Box::new(handler));
+    }
+
+    /// Checks that none of `names`, nor any of them twice, is registered or reserved.
+    fn check<I>(&self, names: I) -> Result<(), Collision>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut seen = Vec::new();
+        for name in names {
+            let reserved = name == CANCEL || introspect::is_reserved(&name);
+            if reserved || self.handlers.contains_key(&name) || seen.contains(&name) {
+                return Err(Collision(name));
+            }
+            seen.push(name);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Methods {
+    fn default() -> Self {
+        Self {
+            separator: DEFAULT_SEPARATOR.to_owned(),
+            handlers: HashMap::new(),
+            descriptions: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Methods {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Methods")
+            .field("separator", &self.separator)
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Params, Server, Value};
+
+    fn echo(_context: &Context, params: Params) -> Result<Value, Error> {
+        Ok(json!(params))
+    }
+
+    fn names(methods: &Methods) -> Vec<String> {
+        methods.names().iter().map(Method::to_string).collect()
+    }
+
+    #[test]
+    fn methods_mount() {
+        let mut user = Methods::new();
+        user.register("get", echo).unwrap();
+        user.register("list", echo).unwrap();
+        user.describe("get", Description::new().with_summary("Gets a user."))
+            .unwrap();
+
+        let mut billing = Methods::new();
+        billing.register("charge", echo).unwrap();
+        let mut admin = Methods::new();
+        admin.mount("billing", billing).unwrap();
+
+        let mut methods = Methods::new();
+        methods.register("ping", echo).unwrap();
+        methods.mount("user", user).unwrap();
+        methods.mount("admin", admin).unwrap();
+
+        let expected = vec!["admin.billing.charge", "ping", "user.get", "user.list"];
+        assert_eq!(names(&methods), expected);
+        assert!(methods.get("user.get").is_some());
+        assert!(methods.get("get").is_none());
+        let description = methods.description("user.get").unwrap();
+        assert_eq!(description.summary.as_deref(), Some("Gets a user."));
+
+        let mut user = Methods::new();
+        user.register("get", echo).unwrap();
+        let mut methods = Methods::new();
+        methods.set_separator("/");
+        methods.mount("user", user).unwrap();
+        assert_eq!(names(&methods), vec!["user/get"]);
+    }
+
+    #[test]
+    fn methods_collision() {
+        let mut methods = Methods::new();
+        methods.register("user.get", echo).unwrap();
+        let result = methods.register("user.get", echo);
+        assert_eq!(result, Err(Collision("user.get".to_owned())));
+
+        // A colliding table is not mounted at all.
+        let mut user = Methods::new();
+        user.register("list", echo).unwrap();
+        user.register("get", echo).unwrap();
+        let result = methods.mount("user", user);
+        assert_eq!(result, Err(Collision("user.get".to_owned())));
+        assert_eq!(names(&methods), vec!["user.get"]);
+
+        // Reserved names, whether registered or mounted.
+        let result = methods.register(CANCEL, echo);
+        assert_eq!(result, Err(Collision(CANCEL.to_owned())));
+        let mut rpc = Methods::new();
+        rpc.register("discover", echo).unwrap();
+        let result = methods.mount("rpc", rpc);
+        assert_eq!(result, Err(Collision("rpc.discover".to_owned())));
+
+        // Only registered methods are described.
+        let result = methods.describe("user.list", Description::new());
+        assert_eq!(result, Err(Unregistered("user.list".to_owned())));
+        assert!(methods.description("user.list").is_none());
+    }
+
+    #[test]
+    fn methods_server_mount() {
+        let mut user = Methods::new();
+        user.register("get", |_: &Context, _params| Ok(Value::from("user")))
+            .unwrap();
+        let mut billing = Methods::new();
+        billing
+            .register("get", |_: &Context, _params| Ok(Value::from("billing")))
+            .unwrap();
+
+        let mut server = Server::new();
+        server.mount("user", user).unwrap();
+        server.mount("billing", billing).unwrap();
+        assert_eq!(server.names().len(), 2);
+
+        let input = r#"[
+            {"id":1,"method":"user.get","params":[]},
+            {"id":2,"method":"billing.get","params":[]},
+            {"id":3,"method":"get","params":[]}
+        ]"#;
+        let expected = concat!(
+            r#"[{"id":1,"result":"user"},{"id":2,"result":"billing"},"#,
+            r#"{"error":{"code":-32601,"message":"Method not found"},"id":3}]"#,
+        );
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+}
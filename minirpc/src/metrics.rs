@@ -0,0 +1,267 @@
+//! MINI-RPC Metrics.
+//!
+//! A [`Metrics`] hook set on a `Server` observes each handled call and notification.
+//! No backend is required: [`InMemory`] aggregates request counts, error counts by
+//! `Code` and latency histograms per method, and renders them in the Prometheus text
+//! exposition format so they can be scraped from any transport.
+//!
+//! The server records the requests of methods which are not registered together, without
+//! their name, which bounds the number of recorded methods (and of Prometheus series)
+//! whatever clients send. They are rendered with a `registered="false"` label instead of
+//! a `method` one, so that they cannot be mistaken for a registered method.
+
+use crate::error::Code;
+use crate::Method;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Metrics hook.
+pub trait Metrics: Send + Sync {
+    /// Records a request of `method` handled in `elapsed`, which failed with `code` if any.
+    ///
+    /// `method` is `None` for the methods which are not registered.
+    fn record(&self, method: Option<&Method>, code: Option<&Code>, elapsed: Duration);
+}
+
+impl<M> Metrics for Arc<M>
+where
+    M: Metrics + ?Sized,
+{
+    fn record(&self, method: Option<&Method>, code: Option<&Code>, elapsed: Duration) {
+        (**self).record(method, code, elapsed)
+    }
+}
+
+/// Default latency histogram buckets, in seconds.
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// In-memory metrics aggregator.
+#[derive(Debug)]
+pub struct InMemory {
+    buckets: Vec<f64>,
+    methods: Mutex<BTreeMap<Option<String>, Stats>>,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    requests: u64,
+    errors: BTreeMap<i64, u64>,
+    buckets: Vec<u64>,
+    seconds: f64,
+}
+
+impl InMemory {
+    /// Creates a new `InMemory` aggregator with the default latency buckets.
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Creates a new `InMemory` aggregator with given latency `buckets` (in seconds).
+    ///
+    /// Buckets which are not finite are ignored, as are duplicates: the `+Inf` bucket is
+    /// always rendered.
+    pub fn with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.retain(|bound| bound.is_finite());
+        buckets.sort_by(f64::total_cmp);
+        buckets.dedup();
+        Self {
+            buckets,
+            methods: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the number of recorded requests of `method`, or of the methods which are
+    /// not registered if `None`.
+    pub fn requests(&self, method: Option<&str>) -> u64 {
+        let methods = self.methods.lock().unwrap();
+        let method = method.map(str::to_owned);
+        methods.get(&method).map_or(0, |stats| stats.requests)
+    }
+
+    /// Returns the number of recorded requests of `method`, or of the methods which are
+    /// not registered if `None`, which failed with `code`.
+    pub fn errors(&self, method: Option<&str>, code: &Code) -> u64 {
+        let methods = self.methods.lock().unwrap();
+        let method = method.map(str::to_owned);
+        methods
+            .get(&method)
+            .and_then(|stats| stats.errors.get(&code.as_i64()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let methods = self.methods.lock().unwrap();
+        let mut output = String::new();
+
+        output.push_str("# HELP minirpc_requests_total Total number of handled requests.\n");
+        output.push_str("# TYPE minirpc_requests_total counter\n");
+        for (method, stats) in methods.iter() {
+            let method = label(method);
+            let _ = writeln!(
+                output,
+                "minirpc_requests_total{{{}}} {}",
+                method, stats.requests
+            );
+        }
+
+        output.push_str("# HELP minirpc_errors_total Total number of failed requests.\n");
+        output.push_str("# TYPE minirpc_errors_total counter\n");
+        for (method, stats) in methods.iter() {
+            let method = label(method);
+            for (code, count) in stats.errors.iter() {
+                let _ = writeln!(
+                    output,
+                    "minirpc_errors_total{{{},code=\"{}\"}} {}",
+                    method, code, count
+                );
+            }
+        }
+
+        output.push_str("# HELP minirpc_request_duration_seconds Time spent handling requests.\n");
+        output.push_str("# TYPE minirpc_request_duration_seconds histogram\n");
+        for (method, stats) in methods.iter() {
+            let method = label(method);
+            for (bound, count) in self.buckets.iter().zip(stats.buckets.iter()) {
+                let _ = writeln!(
+                    output,
+                    "minirpc_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    method, bound, count
+                );
+            }
+            let _ = writeln!(
+                output,
+                "minirpc_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                method, stats.requests
+            );
+            let _ = writeln!(
+                output,
+                "minirpc_request_duration_seconds_sum{{{}}} {}",
+                method, stats.seconds
+            );
+            let _ = writeln!(
+                output,
+                "minirpc_request_duration_seconds_count{{{}}} {}",
+                method, stats.requests
+            );
+        }
+
+        output
+    }
+}
+
+impl Default for InMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics for InMemory {
+    fn record(&self, method: Option<&Method>, code: Option<&Code>, elapsed: Duration) {
+        let mut methods = self.methods.lock().unwrap();
+        let stats = methods
+            .entry(method.map(|method| method.as_str().to_owned()))
+            .or_insert_with(|| Stats {
+                buckets: vec![0; self.buckets.len()],
+                ..Stats::default()
+            });
+
+        let seconds = elapsed.as_secs_f64();
+        stats.requests += 1;
+        stats.seconds += seconds;
+        if let Some(code) = code {
+            *stats.errors.entry(code.as_i64()).or_insert(0) += 1;
+        }
+        for (bound, count) in self.buckets.iter().zip(stats.buckets.iter_mut()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+    }
+}
+
+/// Returns the Prometheus label identifying `method`.
+fn label(method: &Option<String>) -> String {
+    match method {
+        Some(method) => format!("method=\"{}\"", escape(method)),
+        None => "registered=\"false\"".to_owned(),
+    }
+}
+
+/// Escapes a Prometheus label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_record() {
+        let metrics = InMemory::new();
+        let method = Method::String("sum".to_owned());
+
+        metrics.record(Some(&method), None, Duration::from_millis(1));
+        metrics.record(
+            Some(&method),
+            Some(&Code::InvalidParams),
+            Duration::from_millis(1),
+        );
+        metrics.record(
+            Some(&method),
+            Some(&Code::InvalidParams),
+            Duration::from_millis(1),
+        );
+
+        assert_eq!(metrics.requests(Some("sum")), 3);
+        assert_eq!(metrics.requests(None), 0);
+        assert_eq!(metrics.errors(Some("sum"), &Code::InvalidParams), 2);
+        assert_eq!(metrics.errors(Some("sum"), &Code::InternalError), 0);
+    }
+
+    #[test]
+    fn in_memory_render() {
+        let metrics = InMemory::with_buckets(vec![0.5, 0.1, 0.5, f64::INFINITY, f64::NAN]);
+        let method = Method::String("say \"hi\"".to_owned());
+
+        metrics.record(Some(&method), None, Duration::from_millis(50));
+        metrics.record(
+            Some(&method),
+            Some(&Code::ServerError(-32000)),
+            Duration::from_millis(250),
+        );
+        metrics.record(None, Some(&Code::MethodNotFound), Duration::from_millis(1));
+
+        let expected = r#"# HELP minirpc_requests_total Total number of handled requests.
+# TYPE minirpc_requests_total counter
+minirpc_requests_total{registered="false"} 1
+minirpc_requests_total{method="say \"hi\""} 2
+# HELP minirpc_errors_total Total number of failed requests.
+# TYPE minirpc_errors_total counter
+minirpc_errors_total{registered="false",code="-32601"} 1
+minirpc_errors_total{method="say \"hi\"",code="-32000"} 1
+# HELP minirpc_request_duration_seconds Time spent handling requests.
+# TYPE minirpc_request_duration_seconds histogram
+minirpc_request_duration_seconds_bucket{registered="false",le="0.1"} 1
+minirpc_request_duration_seconds_bucket{registered="false",le="0.5"} 1
+minirpc_request_duration_seconds_bucket{registered="false",le="+Inf"} 1
+minirpc_request_duration_seconds_sum{registered="false"} 0.001
+minirpc_request_duration_seconds_count{registered="false"} 1
+minirpc_request_duration_seconds_bucket{method="say \"hi\"",le="0.1"} 1
+minirpc_request_duration_seconds_bucket{method="say \"hi\"",le="0.5"} 2
+minirpc_request_duration_seconds_bucket{method="say \"hi\"",le="+Inf"} 2
+minirpc_request_duration_seconds_sum{method="say \"hi\""} 0.3
+minirpc_request_duration_seconds_count{method="say \"hi\""} 2
+"#;
+        assert_eq!(metrics.render(), expected);
+    }
+}
@@ -0,0 +1,51 @@
+//! MINI-RPC MessagePack encoding.
+//!
+//! Structs are encoded as maps keyed by field name (rather than MessagePack's compact
+//! array form), so untagged `Request` and `Response` payloads resolve exactly as in JSON.
+
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+
+pub use rmp_serde::decode::Error as DecodeError;
+pub use rmp_serde::encode::Error as EncodeError;
+
+/// Serializes given `value` as MessagePack.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: Serialize + ?Sized,
+{
+    rmp_serde::to_vec_named(value)
+}
+
+/// Deserializes an instance of `T` from MessagePack `bytes`.
+pub fn from_slice<T>(bytes: &[u8]) -> Result<T, DecodeError>
+where
+    T: DeserializeOwned,
+{
+    rmp_serde::from_slice(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Id, Success};
+    use serde_json::Value;
+
+    #[test]
+    fn msgpack_named_fields() {
+        let input = Success {
+            id: Id::Number(1),
+            result: Value::Bool(true),
+        };
+        // fixmap(2), "id", 1, "result", true.
+        let expected = vec![
+            0x82, 0xa2, b'i', b'd', 0x01, 0xa6, b'r', b'e', b's', b'u', b'l', b't', 0xc3,
+        ];
+
+        let result = to_vec(&input).unwrap();
+        assert_eq!(result, expected);
+
+        let result: Success = from_slice(&expected).unwrap();
+        assert_eq!(result, input);
+    }
+}
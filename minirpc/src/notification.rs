@@ -3,14 +3,14 @@
 use crate::{Method, Params};
 
 /// Represents a MINI-RPC Request which is a notification.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
-pub struct Notification {
+pub struct Notification<P = Params> {
     /// A String containing the name of the method to be invoked.
     pub method: Method,
 
     /// A Structured value that holds the parameter values to be used during the invocation of the method.
-    pub params: Params,
+    pub params: P,
 }
 
 #[cfg(test)]
@@ -41,4 +41,19 @@ mod tests {
         let result = serde_json::to_string(&input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[cfg(feature = "raw_value")]
+    #[test]
+    fn notification_raw_value_passthrough() {
+        use serde_json::value::RawValue;
+
+        let input = r#"{"method":"test_method","params":[1,  {"a" :2}]}"#;
+
+        let result: Notification<Box<RawValue>> = serde_json::from_str(input).unwrap();
+        assert_eq!(result.method, Method::String("test_method".to_owned()));
+        assert_eq!(result.params.get(), r#"[1,  {"a" :2}]"#);
+
+        let result = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, input);
+    }
 }
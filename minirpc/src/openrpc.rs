@@ -0,0 +1,143 @@
+//! MINI-RPC OpenRPC Documents.
+//!
+//! [`document`] generates the [OpenRPC](https://spec.open-rpc.org) document of the
+//! registered methods from their `Description`: params in order with their name and
+//! schema, whether they are given by position (`Params::Array`), by name
+//! (`Params::Object`) or either, the result schema, and the errors each method can
+//! fail with. Introspection methods are not listed.
+//!
+//! The document is returned by the `rpc.discover` introspection method, and can be
+//! generated with `Server::document` to publish it.
+//!
+//! OpenRPC considers methods without a result as notifications only, so methods whose
+//! result is not described are given a result of any schema.
+
+use crate::introspect::{Description, Info};
+use crate::methods::Methods;
+use crate::Method;
+use serde_json::{Map, Value};
+
+/// Version of the OpenRPC specification of the generated documents.
+pub const OPENRPC: &str = "1.2.6";
+
+/// Returns the OpenRPC document of `methods`, served by the server of given `info`.
+pub fn document(info: &Info, methods: &Methods) -> Value {
+    let methods: Vec<_> = methods
+        .names()
+        .iter()
+        .map(|name| {
+            let description = methods.description(name.as_str());
+            method(name, description.unwrap_or(&Description::default()))
+        })
+        .collect();
+
+    json!({
+        "openrpc": OPENRPC,
+        "info": {
+            "title": info.name,
+            "version": info.version,
+        },
+        "methods": methods,
+    })
+}
+
+/// Returns the OpenRPC method object of `name` of given `description`.
+fn method(name: &Method, description: &Description) -> Value {
+    let mut object = Map::new();
+    object.insert("name".to_owned(), json!(name));
+    if let Some(summary) = &description.summary {
+        object.insert("summary".to_owned(), json!(summary));
+    }
+    object.insert("params".to_owned(), json!(description.params));
+    object.insert(
+        "paramStructure".to_owned(),
+        json!(description.param_structure),
+    );
+    let schema = description.result.clone().unwrap_or_else(|| json!({}));
+    object.insert(
+        "result".to_owned(),
+        json!({"name": "result", "schema": schema}),
+    );
+    if !description.errors.is_empty() {
+        object.insert("errors".to_owned(), json!(description.errors));
+    }
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspect::ParamStructure;
+    use crate::{Context, Error, Server};
+
+    fn server() -> Server {
+        let mut server = Server::new();
+        server
+            .register("sum", |_: &Context, _params| Ok(Value::from(0)))
+            .unwrap();
+        server
+            .register("ping", |_: &Context, _params| Ok(Value::Null))
+            .unwrap();
+        server
+            .describe(
+                "sum",
+                Description::new()
+                    .with_summary("Sums numbers.")
+                    .with_param("a", json!({"type": "integer"}))
+                    .with_optional_param("b", json!({"type": "integer"}))
+                    .with_param_structure(ParamStructure::ByPosition)
+                    .with_result(json!({"type": "integer"}))
+                    .with_error(Error::new_invalid_params())
+                    .with_error(Error::new_rate_limited()),
+            )
+            .unwrap();
+        server
+    }
+
+    #[test]
+    fn openrpc_document() {
+        let mut server = server();
+        assert_eq!(server.document(), None);
+
+        server.set_introspection(Info::new("calculator", "1.0.0"));
+        let result = server.document().unwrap();
+        let expected = json!({
+            "openrpc": "1.2.6",
+            "info": {"title": "calculator", "version": "1.0.0"},
+            "methods": [
+                {
+                    "name": "ping",
+                    "params": [],
+                    "paramStructure": "either",
+                    "result": {"name": "result", "schema": {}},
+                },
+                {
+                    "name": "sum",
+                    "summary": "Sums numbers.",
+                    "params": [
+                        {"name": "a", "schema": {"type": "integer"}, "required": true},
+                        {"name": "b", "schema": {"type": "integer"}, "required": false},
+                    ],
+                    "paramStructure": "by-position",
+                    "result": {"name": "result", "schema": {"type": "integer"}},
+                    "errors": [
+                        {"code": -32602, "message": "Invalid params"},
+                        {"code": -32003, "message": "Rate limited"},
+                    ],
+                },
+            ],
+        });
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn openrpc_discover() {
+        let mut server = server();
+        server.set_introspection(Info::new("calculator", "1.0.0"));
+
+        let input = r#"{"id":1,"method":"rpc.discover","params":[]}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        let expected = json!({"id": 1, "result": server.document()});
+        assert_eq!(json!(result), expected);
+    }
+}
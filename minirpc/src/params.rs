@@ -3,7 +3,7 @@
 use serde_json::{Map, Value};
 
 /// Request parameters.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Params {
     /// Array of values.
@@ -0,0 +1,225 @@
+//! MINI-RPC Authorization Policies.
+//!
+//! A [`Policy`] maps method names, or prefixes such as `admin.*`, to the permissions a
+//! `Principal` needs to invoke them. As an interceptor, it is evaluated before dispatch:
+//! denied calls are answered with `Error::new_forbidden()`, denied notifications are
+//! dropped. Either way, the hook set with [`Policy::set_on_denied`] is invoked, so that
+//! denials are reported even though notifications get no answer. They are also logged
+//! with the `tracing` feature.
+
+use crate::auth::Principal;
+use crate::interceptor::Interceptor;
+use crate::{Call, Context, Error, Method, Notification};
+use std::fmt;
+use std::sync::Arc;
+
+/// Method name pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pattern {
+    /// Matches the exact method name.
+    Exact(String),
+
+    /// Matches every method name starting with the prefix.
+    Prefix(String),
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Self {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => Pattern::Prefix(prefix.to_owned()),
+            None => Pattern::Exact(pattern.to_owned()),
+        }
+    }
+
+    /// Returns how specific the match of `method` is, if it matches.
+    fn matches(&self, method: &str) -> Option<usize> {
+        match self {
+            Pattern::Exact(name) if name == method => Some(usize::MAX),
+            Pattern::Prefix(prefix) if method.starts_with(prefix.as_str()) => Some(prefix.len()),
+            _ => None,
+        }
+    }
+}
+
+/// Hook invoked with the context and method of a denied call or notification.
+type OnDenied = Arc<dyn Fn(&Context, &Method) + Send + Sync>;
+
+/// Permissions required per method.
+///
+/// When several rules match a method, the most specific one applies: an exact name,
+/// then the longest prefix. Methods matched by no rule require no permission; add a
+/// `*` rule to deny them by default.
+#[derive(Clone, Default)]
+pub struct Policy {
+    rules: Vec<(Pattern, Vec<String>)>,
+    on_denied: Option<OnDenied>,
+}
+
+impl Policy {
+    /// Creates a new, empty `Policy`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires all of `permissions` to invoke the methods matching `pattern`, which is
+    /// either a method name or a prefix ending with `*`.
+    pub fn require(&mut self, pattern: &str, permissions: &[&str]) {
+        let permissions = permissions.iter().map(|&p| p.to_owned()).collect();
+        self.rules.push((Pattern::parse(pattern), permissions));
+    }
+
+    /// Sets the hook invoked with the context and method of each denied call or
+    /// notification.
+    pub fn set_on_denied<F>(&mut self, hook: F)
+    where
+        F: Fn(&Context, &Method) + Send + Sync + 'static,
+    {
+        self.on_denied = Some(Arc::new(hook));
+    }
+
+    /// Returns the permissions required to invoke `method`.
+    pub fn permissions(&self, method: &str) -> &[String] {
+        self.rules
+            .iter()
+            .filter_map(|(pattern, permissions)| Some((pattern.matches(method)?, permissions)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(&[], |(_, permissions)| permissions.as_slice())
+    }
+
+    /// Checks that `principal` may invoke `method`.
+    pub fn authorize(&self, principal: Option<&Principal>, method: &Method) -> Result<(), Error> {
+        let permissions = self.permissions(method.as_str());
+        if permissions.is_empty() {
+            return Ok(());
+        }
+
+        match principal {
+            Some(principal) if permissions.iter().all(|p| principal.has_permission(p)) => Ok(()),
+            _ => Err(Error::new_forbidden()),
+        }
+    }
+
+    /// Checks that the principal of `context` may invoke `method`, reporting denials.
+    fn check(&self, context: &Context, method: &Method) -> Result<(), Error> {
+        let result = self.authorize(context.get(), method);
+        if result.is_err() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(method = %method, "Method denied");
+
+            if let Some(hook) = &self.on_denied {
+                hook(context, method);
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Policy")
+            .field("rules", &self.rules)
+            .field("on_denied", &self.on_denied.is_some())
+            .finish()
+    }
+}
+
+impl Interceptor for Policy {
+    fn before_call(&self, context: &mut Context, call: &mut Call) -> Result<(), Error> {
+        self.check(context, &call.method)
+    }
+
+    fn before_notification(
+        &self,
+        context: &mut Context,
+        notification: &mut Notification,
+    ) -> Result<(), Error> {
+        self.check(context, &notification.method)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Code, FORBIDDEN};
+    use crate::metrics::InMemory;
+    use crate::{Params, Server};
+    use serde_json::Value;
+    use std::sync::Mutex;
+
+    fn policy() -> Policy {
+        let mut policy = Policy::new();
+        policy.require("admin.*", &["admin"]);
+        policy.require("admin.audit.*", &["auditor"]);
+        policy.require("admin.status", &[]);
+        policy
+    }
+
+    #[test]
+    fn policy_permissions() {
+        let policy = policy();
+
+        assert_eq!(policy.permissions("admin.reboot"), ["admin"]);
+        assert_eq!(policy.permissions("admin.audit.list"), ["auditor"]);
+        assert!(policy.permissions("admin.status").is_empty());
+        assert!(policy.permissions("sum").is_empty());
+    }
+
+    #[test]
+    fn policy_authorize() {
+        let policy = policy();
+        let method = Method::String("admin.reboot".to_owned());
+        let admin = Principal::new("alice").with_permission("admin");
+        let guest = Principal::new("bob");
+
+        assert_eq!(policy.authorize(Some(&admin), &method), Ok(()));
+        assert_eq!(
+            policy.authorize(Some(&guest), &method),
+            Err(Error::new_forbidden())
+        );
+        assert_eq!(policy.authorize(None, &method), Err(Error::new_forbidden()));
+
+        let method = Method::String("sum".to_owned());
+        assert_eq!(policy.authorize(None, &method), Ok(()));
+    }
+
+    #[test]
+    fn policy_intercept() {
+        let metrics = Arc::new(InMemory::new());
+        let denied = Arc::new(Mutex::new(Vec::new()));
+        let mut policy = policy();
+        let hook = denied.clone();
+        policy.set_on_denied(move |_: &Context, method: &Method| {
+            hook.lock().unwrap().push(method.to_string());
+        });
+        let mut server = Server::new();
+        server.set_metrics(metrics.clone());
+        server.intercept(policy);
+        server
+            .register("admin.reboot", |_: &Context, _: Params| {
+                Ok(Value::Bool(true))
+            })
+            .unwrap();
+
+        let input = r#"{"id":1,"method":"admin.reboot","params":[]}"#;
+
+        let expected = r#"{"error":{"code":-32002,"message":"Forbidden"},"id":1}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let mut context = Context::new();
+        context.insert(Principal::new("alice").with_permission("admin"));
+
+        let expected = r#"{"id":1,"result":true}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        // Denied notifications are dropped, but reported.
+        let input = r#"{"method":"admin.reboot","params":[]}"#;
+        assert_eq!(server.handle(serde_json::from_str(input).unwrap()), None);
+        assert_eq!(*denied.lock().unwrap(), ["admin.reboot", "admin.reboot"]);
+        assert_eq!(
+            metrics.errors(Some("admin.reboot"), &Code::ServerError(FORBIDDEN)),
+            2
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! MINI-RPC Progress Reports.
+//!
+//! While working on a call, a handler reports its progress through the [`Progress`] of
+//! its `Context`, which the server provides when the transport inserted an `Outbox`.
+//! Each report is sent as a [`PROGRESS`] notification whose params hold the call `id`
+//! and the reported `value`, which the client exposes through the `Reply` of the call.
+
+use crate::context::Outbox;
+use crate::shape;
+use crate::{Id, Method, Notification};
+use serde_json::Value;
+
+/// Method of the notification reporting the progress of a call.
+pub const PROGRESS: &str = "$/progress";
+
+/// Progress reporter of a call.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    id: Id,
+    outbox: Outbox,
+}
+
+impl Progress {
+    /// Creates a new `Progress` reporting on call `id` through `outbox`.
+    pub fn new(id: Id, outbox: Outbox) -> Self {
+        Self { id, outbox }
+    }
+
+    /// Reports given progress `value`, returning `false` once the peer is gone.
+    pub fn report(&self, value: Value) -> bool {
+        self.outbox.send(Notification {
+            method: Method::String(PROGRESS.to_owned()),
+            params: shape::PROGRESS.params(&self.id, value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn progress_report() {
+        let (sender, receiver) = mpsc::channel();
+        let outbox = Outbox::new(move |notification| sender.send(notification).is_ok());
+
+        Progress::new(Id::Number(1), outbox).report(Value::from(50));
+
+        let expected = r#"{"method":"$/progress","params":{"id":1,"value":50}}"#;
+        let result = receiver.try_recv().unwrap();
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+}
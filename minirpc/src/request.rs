@@ -1,33 +1,35 @@
 //! MINI-RPC Request.
 
-use crate::{Call, Notification};
+use crate::{Call, Notification, Params};
 
 /// Request.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+///
+/// Params default to [`Params`], but any representation can be carried by setting `P`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum Request {
+pub enum Request<P = Params> {
     /// A batch of requests (payloads).
-    Batch(Vec<Payload>),
+    Batch(Vec<Payload<P>>),
 
     /// A single request (payload).
-    Single(Payload),
+    Single(Payload<P>),
 }
 
 /// Request payload.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum Payload {
+pub enum Payload<P = Params> {
     /// Fire a notification.
-    Notification(Notification),
+    Notification(Notification<P>),
 
     /// Call a method.
-    Call(Call),
+    Call(Call<P>),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Call, Id, Method, Notification, Params};
+    use crate::{Call, Id, Method, Notification};
     use serde_json::{self, Value};
 
     #[test]
@@ -48,6 +50,7 @@ mod tests {
             id: Id::Number(1),
             method: Method::String("test_method".to_owned()),
             params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+            deadline: None,
         }));
 
         let result: Request = serde_json::from_str(input).unwrap();
@@ -65,6 +68,7 @@ mod tests {
                 id: Id::Number(1),
                 method: Method::String("test_method".to_owned()),
                 params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+                deadline: None,
             }),
         ]);
 
@@ -89,6 +93,7 @@ mod tests {
             id: Id::Number(1),
             method: Method::String("test_method".to_owned()),
             params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+            deadline: None,
         }));
         let expected = r#"{"id":1,"method":"test_method","params":[1,2,3]}"#;
 
@@ -105,6 +110,7 @@ mod tests {
                 id: Id::Number(1),
                 method: Method::String("test_method".to_owned()),
                 params: Params::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+                deadline: None,
             }),
         ]);
         let expected =
@@ -113,4 +119,15 @@ mod tests {
         let result = serde_json::to_string(&input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn request_round_trip() {
+        let inputs = [
+            r#"{"method":"test_method","params":[1,2,3]}"#,
+            r#"{"id":1,"method":"test_method","params":[1,2,3]}"#,
+            r#"{"method":"test_method","params":{"foo":"bar"}}"#,
+            r#"[{"method":"test_method","params":[1,2,3]},{"id":1,"method":"test_method","params":[1,2,3]}]"#,
+        ];
+        crate::codec::assert_round_trip::<Request>(&inputs);
+    }
 }
@@ -1,34 +1,38 @@
 //! MINI-RPC Response.
 
 use crate::{Failure, Success};
+use serde_json::Value;
 
 /// Response payload.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum Payload {
+pub enum Payload<R = Value, D = Value> {
     /// Unsuccessful response.
-    Failure(Failure),
+    Failure(Failure<D>),
 
     /// Successful response.
-    Success(Success),
+    Success(Success<R>),
 }
 
 /// Response.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+///
+/// Results and error data default to [`Value`], but any representation can be carried
+/// by setting `R` and `D`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum Response {
+pub enum Response<R = Value, D = Value> {
     /// A batch of responses (payloads).
-    Batch(Vec<Payload>),
+    Batch(Vec<Payload<R, D>>),
 
     /// A single response (payload).
-    Single(Payload),
+    Single(Payload<R, D>),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{Error, Id};
-    use serde_json::{self, Value};
+    use serde_json;
 
     #[test]
     fn response_deserialization() {
@@ -73,7 +77,7 @@ mod tests {
     #[test]
     fn response_serialization() {
         // Single Failure.
-        let input = Response::Single(Payload::Failure(Failure {
+        let input: Response = Response::Single(Payload::Failure(Failure {
             error: Error::new_parse_error(),
             id: Some(Id::Number(1)),
         }));
@@ -83,7 +87,7 @@ mod tests {
         assert_eq!(result, expected);
 
         // Single Success.
-        let input = Response::Single(Payload::Success(Success {
+        let input: Response = Response::Single(Payload::Success(Success {
             id: Id::Number(1),
             result: Value::Bool(true),
         }));
@@ -109,4 +113,15 @@ mod tests {
         let result = serde_json::to_string(&input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn response_round_trip() {
+        let inputs = [
+            r#"{"error":{"code":-32700,"message":"Parse error"},"id":1}"#,
+            r#"{"error":{"code":-32000,"message":"Server error","data":[1]}}"#,
+            r#"{"id":1,"result":true}"#,
+            r#"[{"error":{"code":-32700,"message":"Parse error"},"id":1},{"id":1,"result":true}]"#,
+        ];
+        crate::codec::assert_round_trip::<Response>(&inputs);
+    }
 }
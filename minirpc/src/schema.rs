@@ -0,0 +1,292 @@
+//! MINI-RPC JSON Schema Validation.
+//!
+//! [`validate`] checks a value against a JSON Schema and returns every [`Violation`],
+//! located by the JSON Pointer of the offending value. The supported subset covers the
+//! keywords describing plain data:
+//!
+//! - `type` (one or several), `enum` and `const`;
+//! - `minimum`, `maximum`, `exclusiveMinimum` and `exclusiveMaximum`;
+//! - `minLength` and `maxLength`;
+//! - `items`, `minItems` and `maxItems`;
+//! - `properties`, `required` and `additionalProperties`;
+//! - `allOf`, `anyOf`, `oneOf` and `not`.
+//!
+//! Other keywords are ignored, as are keywords whose value is not of the expected type.
+
+use serde_json::{Map, Value};
+
+/// Violation of a schema.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Violation {
+    /// JSON Pointer of the offending value.
+    pub path: String,
+
+    /// What the value violates.
+    pub message: String,
+}
+
+impl Violation {
+    /// Creates a new `Violation` of the value at `path`.
+    pub fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+/// Returns the violations of `schema` by `value`, which is valid when there is none.
+pub fn validate(schema: &Value, value: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check(schema, value, "", &mut violations);
+    violations
+}
+
+/// Returns `path` extended with the JSON Pointer reference `token`.
+pub fn pointer(path: &str, token: &str) -> String {
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn check(schema: &Value, value: &Value, path: &str, violations: &mut Vec<Violation>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            violations.push(Violation::new(path, "no value is allowed"));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    let mut violate = |message: String| violations.push(Violation::new(path, &message));
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<_> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| is_type(value, name)) {
+            violate(format!("expected {}", types.join(" or ")));
+        }
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.contains(value) {
+            violate(format!("expected one of {}", Value::Array(values.clone())));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violate(format!("expected {}", expected));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        let bound = |keyword| schema.get(keyword).and_then(Value::as_f64);
+        if let Some(minimum) = bound("minimum").filter(|&minimum| number < minimum) {
+            violate(format!("expected at least {}", minimum));
+        }
+        if let Some(maximum) = bound("maximum").filter(|&maximum| number > maximum) {
+            violate(format!("expected at most {}", maximum));
+        }
+        if let Some(minimum) = bound("exclusiveMinimum").filter(|&minimum| number <= minimum) {
+            violate(format!("expected more than {}", minimum));
+        }
+        if let Some(maximum) = bound("exclusiveMaximum").filter(|&maximum| number >= maximum) {
+            violate(format!("expected less than {}", maximum));
+        }
+    }
+
+    if let Value::String(string) = value {
+        let length = string.chars().count() as u64;
+        check_length(schema, "Length", length, "characters", &mut violate);
+    }
+
+    if let Value::Array(values) = value {
+        check_length(schema, "Items", values.len() as u64, "items", &mut violate);
+        if let Some(items) = schema.get("items") {
+            for (index, item) in values.iter().enumerate() {
+                check(items, item, &pointer(path, &index.to_string()), violations);
+            }
+        }
+    }
+
+    if let Value::Object(map) = value {
+        check_object(schema, map, path, violations);
+    }
+
+    check_combinations(schema, value, path, violations);
+}
+
+fn check_length<F>(
+    schema: &Map<String, Value>,
+    keyword: &str,
+    length: u64,
+    unit: &str,
+    violate: &mut F,
+) where
+    F: FnMut(String),
+{
+    let bound = |prefix| {
+        schema
+            .get(&format!("{}{}", prefix, keyword))
+            .and_then(Value::as_u64)
+    };
+    if let Some(minimum) = bound("min").filter(|&minimum| length < minimum) {
+        violate(format!("expected at least {} {}", minimum, unit));
+    }
+    if let Some(maximum) = bound("max").filter(|&maximum| length > maximum) {
+        violate(format!("expected at most {} {}", maximum, unit));
+    }
+}
+
+fn check_object(
+    schema: &Map<String, Value>,
+    map: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !map.contains_key(name) {
+                violations.push(Violation::new(&pointer(path, name), "missing property"));
+            }
+        }
+    }
+
+    for (name, value) in map {
+        let path = pointer(path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(schema) => check(schema, value, &path, violations),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    violations.push(Violation::new(&path, "unexpected property"))
+                }
+                Some(additional) => check(additional, value, &path, violations),
+                None => {}
+            },
+        }
+    }
+}
+
+fn check_combinations(
+    schema: &Map<String, Value>,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let schemas = |keyword| {
+        schema
+            .get(keyword)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+    };
+
+    if let Some(schemas) = schemas("allOf") {
+        for schema in schemas {
+            check(schema, value, path, violations);
+        }
+    }
+    if let Some(schemas) = schemas("anyOf") {
+        if !schemas
+            .iter()
+            .any(|schema| validate(schema, value).is_empty())
+        {
+            violations.push(Violation::new(path, "expected to match any schema"));
+        }
+    }
+    if let Some(schemas) = schemas("oneOf") {
+        let count = schemas
+            .iter()
+            .filter(|schema| validate(schema, value).is_empty())
+            .count();
+        if count != 1 {
+            violations.push(Violation::new(path, "expected to match exactly one schema"));
+        }
+    }
+    if let Some(schema) = schema.get("not") {
+        if validate(schema, value).is_empty() {
+            violations.push(Violation::new(path, "expected not to match schema"));
+        }
+    }
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(schema: Value, value: Value) -> Vec<String> {
+        validate(&schema, &value)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect()
+    }
+
+    #[test]
+    fn schema_validate() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "maxItems": 2},
+                "a/b": {"type": ["string", "null"]},
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false,
+        });
+
+        let input = json!({"name": "x", "age": 3, "tags": ["a"], "a/b": null});
+        assert_eq!(validate(&schema, &input), vec![]);
+
+        let input = json!({"name": "", "age": 1.5, "tags": ["a", "c", "b"], "a/b": 1, "x": 0});
+        let result = validate(&schema, &input);
+        let expected = vec![
+            Violation::new("/a~1b", "expected string or null"),
+            Violation::new("/age", "expected integer"),
+            Violation::new("/name", "expected at least 1 characters"),
+            Violation::new("/tags", "expected at most 2 items"),
+            Violation::new("/tags/1", r#"expected one of ["a","b"]"#),
+            Violation::new("/x", "unexpected property"),
+        ];
+        assert_eq!(result, expected);
+
+        assert_eq!(paths(schema, json!({})), vec!["/name", "/age"]);
+    }
+
+    #[test]
+    fn schema_combinations() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"type": "integer"}]});
+        assert!(paths(schema.clone(), json!(1)).is_empty());
+        assert_eq!(paths(schema, json!(true)), vec![""]);
+
+        let schema = json!({"oneOf": [{"type": "number"}, {"type": "integer"}]});
+        assert!(paths(schema.clone(), json!(1.5)).is_empty());
+        assert_eq!(paths(schema, json!(1)), vec![""]);
+
+        let schema = json!({"allOf": [{"minimum": 1}, {"maximum": 3}], "not": {"const": 2}});
+        assert!(paths(schema.clone(), json!(3)).is_empty());
+        assert_eq!(paths(schema.clone(), json!(2)), vec![""]);
+        assert_eq!(paths(schema, json!(4)), vec![""]);
+
+        assert!(paths(json!(true), json!(1)).is_empty());
+        assert_eq!(paths(json!(false), json!(1)), vec![""]);
+    }
+}
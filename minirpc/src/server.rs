@@ -0,0 +1,778 @@
+//! MINI-RPC Server.
+//!
+//! A [`Server`] maps method names to handlers and answers each `Request` with the
+//! matching `Response`. It is transport agnostic: decode a `Request` (enforcing
+//! `Limits` there, as the server never sees the raw bytes), call
+//! [`Server::handle_with`] with the `Context` of the connection, then encode the
+//! returned `Response` (if any).
+//!
+//! Methods are registered one by one, by service, or grouped in `Methods` tables
+//! mounted under a prefix (e.g. `user.get`, `user.list`). With introspection enabled,
+//! clients can list and describe them with the reserved `rpc.*` methods. With
+//! validation enabled, params are checked against these descriptions before the
+//! handler runs.
+//!
+//! With the `tracing` feature, each call and notification runs in an `rpc.call` or
+//! `rpc.notification` span (with `method` and `id` fields), nested in an `rpc.batch`
+//! span for batches. Failures record their `code` and `message` on the call span.
+//!
+//! In non-strict mode, the `deadline` a call carries is set on its `Context`. Calls
+//! whose deadline passes before they complete are answered with
+//! `Error::new_deadline_exceeded()`, whatever the handler returns.
+//!
+//! `$/cancel` notifications cancel the in-flight call of the same `Connection` whose
+//! id they carry: its handler is signaled through the `CancellationToken` of its
+//! `Context`, and the call is answered with `Error::new_cancelled()`.
+//!
+//! When the transport inserted an `Outbox` in the `Context`, handlers find a `Progress`
+//! in the context of each call, to report on it with `$/progress` notifications, and
+//! `Chunks` to stream its result with `$/chunk` notifications.
+
+use crate::cancel::{Registry, CANCEL};
+use crate::chunk::Chunks;
+use crate::context::{Connection, Outbox};
+use crate::interceptor::{Chain, Interceptor};
+use crate::introspect::{self, Description, Info};
+use crate::methods::{Collision, Methods, Unregistered};
+use crate::metrics::Metrics;
+use crate::openrpc;
+use crate::progress::Progress;
+use crate::service::Service;
+use crate::shape;
+use crate::throttle::InFlight;
+use crate::{
+    Call, Context, Error, Failure, Method, Notification, Params, Request, RequestPayload, Response,
+    ResponsePayload, Success,
+};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Method handler.
+pub trait Handler: Send + Sync {
+    /// Invokes the method with given `params`.
+    fn handle(&self, context: &Context, params: Params) -> Result<Value, Error>;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&Context, Params) -> Result<Value, Error> + Send + Sync,
+{
+    fn handle(&self, context: &Context, params: Params) -> Result<Value, Error> {
+        self(context, params)
+    }
+}
+
+/// Dispatches requests to registered handlers.
+pub struct Server {
+    methods: Methods,
+    interceptors: Chain,
+    metrics: Option<Box<dyn Metrics>>,
+    strict: bool,
+    in_flight: Registry,
+    info: Option<Info>,
+    validation: bool,
+}
+
+impl Server {
+    /// Creates a new `Server` without any method.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers given `handler` for `method`, unless already registered or reserved.
+    pub fn register<H>(&mut self, method: &str, handler: H) -> Result<(), Collision>
+    where
+        H: Handler + 'static,
+    {
+        self.methods.register(method, handler)
+    }
+
+    /// Registers the methods of given `service`, unless any is already registered.
+    pub fn serve<S>(&mut self, service: S) -> Result<(), Collision>
+    where
+        S: Service + 'static,
+    {
+        self.methods.serve(service)
+    }
+
+    /// Registers the methods of given `methods` under `prefix`, separated by `.` unless
+    /// set otherwise with [`Server::set_separator`].
+    pub fn mount(&mut self, prefix: &str, methods: Methods) -> Result<(), Collision> {
+        self.methods.mount(prefix, methods)
+    }
+
+    /// Sets the separator between the prefix of a mounted table and its names.
+    pub fn set_separator(&mut self, separator: &str) {
+        self.methods.set_separator(separator);
+    }
+
+    /// Returns the fully-qualified names of the registered methods, in order.
+    pub fn names(&self) -> Vec<Method> {
+        self.methods.names()
+    }
+
+    /// Sets the description of registered `method`, returned by introspection.
+    pub fn describe(&mut self, method: &str, description: Description) -> Result<(), Unregistered> {
+        self.methods.describe(method, description)
+    }
+
+    /// Returns the OpenRPC document of the registered methods, as served by the
+    /// `rpc.discover` method, if introspection is enabled.
+    pub fn document(&self) -> Option<Value> {
+        let info = self.info.as_ref()?;
+        Some(openrpc::document(info, &self.methods))
+    }
+
+    /// Enables the introspection methods, describing the server with given `info`.
+    pub fn set_introspection(&mut self, info: Info) {
+        self.info = Some(info);
+    }
+
+    /// Sets whether params are validated against the description of their method before
+    /// invoking it (disabled by default).
+    pub fn set_validation(&mut self, validation: bool) {
+        self.validation = validation;
+    }
+
+    /// Appends given `interceptor` to the ones run around each invocation.
+    pub fn intercept<I>(&mut self, interceptor: I)
+    where
+        I: Interceptor + 'static,
+    {
+        self.interceptors.push(interceptor);
+    }
+
+    /// Sets the hook recording metrics of each handled call and notification.
+    pub fn set_metrics<M>(&mut self, metrics: M)
+    where
+        M: Metrics + 'static,
+    {
+        self.metrics = Some(Box::new(metrics));
+    }
+
+    /// Sets whether calls must use the standard envelope (the default), or may carry a
+    /// deadline.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Handles given `request` with an empty context.
+    ///
+    /// Returns `None` when there is nothing to answer, i.e. the request only holds
+    /// notifications.
+    pub fn handle(&self, request: Request) -> Option<Response> {
+        self.handle_with(&Context::new(), request)
+    }
+
+    /// Handles given `request` with given `context`.
+    ///
+    /// Each payload is handled with its own copy of the context, so extensions set
+    /// while handling one payload of a batch do not leak into the others.
+    pub fn handle_with(&self, context: &Context, request: Request) -> Option<Response> {
+        match request {
+            Request::Single(payload) => self
+                .handle_payload(context.clone(), payload)
+                .map(Response::Single),
+            Request::Batch(ref payloads) if payloads.is_empty() => {
+                Some(Response::Single(ResponsePayload::Failure(Failure {
+                    error: Error::new_invalid_request(),
+                    id: None,
+                })))
+            }
+            Request::Batch(payloads) => {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::info_span!("rpc.batch", size = payloads.len()).entered();
+
+                let payloads: Vec<_> = payloads
+                    .into_iter()
+                    .filter_map(|payload| self.handle_payload(context.clone(), payload))
+                    .collect();
+                if payloads.is_empty() {
+                    None
+                } else {
+                    Some(Response::Batch(payloads))
+                }
+            }
+        }
+    }
+
+    /// Handles given request `payload` with given `context`.
+    pub fn handle_payload(
+        &self,
+        mut context: Context,
+        payload: RequestPayload,
+    ) -> Option<ResponsePayload> {
+        match payload {
+            RequestPayload::Call(call) => Some(self.call(&mut context, call)),
+            RequestPayload::Notification(notification) => {
+                self.notify(&mut context, notification);
+                None
+            }
+        }
+    }
+
+    fn call(&self, context: &mut Context, call: Call) -> ResponsePayload {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "rpc.call",
+            method = %call.method,
+            id = %call.id,
+            code = tracing::field::Empty,
+            message = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let start = Instant::now();
+        let method = self.metrics.as_ref().map(|_| self.metric(&call.method));
+        let payload = self.dispatch(context, call);
+
+        if let (Some(metrics), Some(method)) = (&self.metrics, method) {
+            let code = match &payload {
+                ResponsePayload::Success(_) => None,
+                ResponsePayload::Failure(failure) => Some(&failure.error.code),
+            };
+            metrics.record(method.as_ref(), code, start.elapsed());
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            if let ResponsePayload::Failure(failure) = &payload {
+                span.record("code", failure.error.code.as_i64());
+                span.record("message", failure.error.message.as_str());
+            }
+        }
+
+        payload
+    }
+
+    fn dispatch(&self, context: &mut Context, mut call: Call) -> ResponsePayload {
+        if let Some(deadline) = call.deadline {
+            if self.strict {
+                return ResponsePayload::Failure(Failure {
+                    error: Error::new_invalid_request(),
+                    id: Some(call.id),
+                });
+            }
+            // A deadline too far to be represented is no deadline.
+            let deadline = Instant::now().checked_add(Duration::from_millis(deadline));
+            match (deadline, context.deadline()) {
+                (Some(deadline), Some(current)) if deadline >= current => {}
+                (Some(deadline), _) => context.set_deadline(deadline),
+                (None, _) => {}
+            }
+        }
+
+        if let Err(failure) = self.interceptors.before_call(context, &mut call) {
+            return ResponsePayload::Failure(failure);
+        }
+        // Hold the throttle slots until the call is answered, whatever handlers do with
+        // clones of the context.
+        let _in_flight = context.take::<InFlight>();
+
+        let connection = context.get::<Connection>().copied();
+        let token = self.in_flight.insert(connection, call.id.clone());
+        context.insert(token.clone());
+        if let Some(outbox) = context.get::<Outbox>().cloned() {
+            context.insert(Progress::new(call.id.clone(), outbox.clone()));
+            context.insert(Chunks::new(call.id.clone(), outbox));
+        }
+
+        // The caller no longer waits for the result of an interrupted call, whether it
+        // was interrupted before or while the handler ran.
+        let interrupted = |context: &Context| {
+            if token.is_cancelled() {
+                Some(Error::new_cancelled())
+            } else if context.is_expired() {
+                Some(Error::new_deadline_exceeded())
+            } else {
+                None
+            }
+        };
+        let result = match interrupted(context) {
+            Some(error) => Err(error),
+            None => self.invoke(context, call.method.as_str(), call.params),
+        };
+        self.in_flight.remove(connection, call.id.clone(), &token);
+        let result = interrupted(context).map_or(result, Err);
+
+        let mut payload = match result {
+            Ok(result) => ResponsePayload::Success(Success {
+                id: call.id,
+                result,
+            }),
+            Err(error) => ResponsePayload::Failure(Failure {
+                error,
+                id: Some(call.id),
+            }),
+        };
+        self.interceptors.after(context, &mut payload);
+        payload
+    }
+
+    fn notify(&self, context: &mut Context, mut notification: Notification) {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("rpc.notification", method = %notification.method).entered();
+
+        let start = Instant::now();
+        let result = self
+            .interceptors
+            .before_notification(context, &mut notification);
+        let _in_flight = context.take::<InFlight>();
+        let Notification { method, params } = notification;
+        let result = result.and_then(|_| match method.as_str() {
+            CANCEL => {
+                let (id, _) = shape::CANCEL.parse(params)?;
+                self.in_flight.cancel(context.get().copied(), id);
+                Ok(Value::Null)
+            }
+            method => self.invoke(context, method, params),
+        });
+
+        // Notifications are never answered, even on failure.
+        if let Some(metrics) = &self.metrics {
+            let code = result.err().map(|error| error.code);
+            metrics.record(
+                self.metric(&method).as_ref(),
+                code.as_ref(),
+                start.elapsed(),
+            );
+        }
+    }
+
+    /// Returns the method under which metrics of `method` are recorded: unknown methods
+    /// are all recorded without name, so clients cannot grow the recorded names.
+    fn metric(&self, method: &Method) -> Option<Method> {
+        let name = method.as_str();
+        let known = name == CANCEL
+            || self.methods.get(name).is_some()
+            || (self.info.is_some() && introspect::is_reserved(name));
+        if known {
+            Some(method.clone())
+        } else {
+            None
+        }
+    }
+
+    fn invoke(&self, context: &Context, method: &str, params: Params) -> Result<Value, Error> {
+        if let Some(info) = &self.info {
+            if introspect::is_reserved(method) {
+                return introspect::handle(info, &self.methods, method, params);
+            }
+        }
+        let handler = self
+            .methods
+            .get(method)
+            .ok_or_else(Error::new_method_not_found)?;
+        if self.validation {
+            if let Some(description) = self.methods.description(method) {
+                description.validate(&params)?;
+            }
+        }
+        handler.handle(context, params)
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            methods: Methods::new(),
+            interceptors: Chain::new(),
+            metrics: None,
+            strict: true,
+            in_flight: Registry::default(),
+            info: None,
+            validation: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancellationToken;
+    use crate::context::Peer;
+    use crate::error::Code;
+    use crate::metrics::InMemory;
+    use crate::{Id, Method};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn sum(_context: &Context, params: Params) -> Result<Value, Error> {
+        match params {
+            Params::Array(values) => {
+                Ok(values.iter().filter_map(Value::as_i64).sum::<i64>().into())
+            }
+            Params::Object(_) => Err(Error::new_invalid_params()),
+        }
+    }
+
+    fn server() -> Server {
+        let mut server = Server::new();
+        server.register("sum", sum).unwrap();
+        server
+    }
+
+    #[test]
+    fn server_handle() {
+        let input = r#"{"id":1,"method":"sum","params":[1,2,3]}"#;
+        let expected = r#"{"id":1,"result":6}"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let input = r#"{"id":1,"method":"sum","params":{}}"#;
+        let expected = r#"{"error":{"code":-32602,"message":"Invalid params"},"id":1}"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let input = r#"{"id":1,"method":"unknown","params":[]}"#;
+        let expected = r#"{"error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn server_handle_batch() {
+        let input = r#"[{"method":"sum","params":[1]},{"id":1,"method":"sum","params":[1,2]}]"#;
+        let expected = r#"[{"id":1,"result":3}]"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let input = r#"[{"method":"sum","params":[1]}]"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(result, None);
+
+        let input = r#"[]"#;
+        let expected = r#"{"error":{"code":-32600,"message":"Invalid request"}}"#;
+
+        let result = server().handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn server_intercept() {
+        struct Rewrite(Arc<AtomicUsize>);
+
+        impl Interceptor for Rewrite {
+            fn before_call(&self, _context: &mut Context, call: &mut Call) -> Result<(), Error> {
+                call.method = Method::String("sum".to_owned());
+                Ok(())
+            }
+
+            fn before_notification(
+                &self,
+                _context: &mut Context,
+                _notification: &mut Notification,
+            ) -> Result<(), Error> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(Error::new_internal_error())
+            }
+
+            fn after_success(&self, _context: &Context, success: &mut Success) {
+                success.result = Value::from(success.result.as_i64().unwrap() * 10);
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut server = server();
+        server.intercept(Rewrite(count.clone()));
+
+        let input = r#"{"id":1,"method":"add","params":[1,2,3]}"#;
+        let expected = Response::Single(ResponsePayload::Success(Success {
+            id: Id::Number(1),
+            result: Value::from(60),
+        }));
+
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(result, Some(expected));
+
+        let input = r#"{"method":"sum","params":[1,2,3]}"#;
+
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(result, None);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn server_context() {
+        struct Tag;
+
+        impl Interceptor for Tag {
+            fn before_call(&self, context: &mut Context, call: &mut Call) -> Result<(), Error> {
+                context.insert(call.method.clone());
+                Ok(())
+            }
+        }
+
+        let mut server = Server::new();
+        server.intercept(Tag);
+        server
+            .register("whoami", |context: &Context, _params| {
+                let peer = context
+                    .get::<Peer>()
+                    .ok_or_else(Error::new_internal_error)?;
+                let method = context
+                    .get::<Method>()
+                    .ok_or_else(Error::new_internal_error)?;
+                Ok(Value::from(format!("{} {}", peer.0, method)))
+            })
+            .unwrap();
+
+        let mut context = Context::new();
+        context.insert(Peer("127.0.0.1:4000".to_owned()));
+
+        let input = r#"{"id":1,"method":"whoami","params":[]}"#;
+        let expected = r#"{"id":1,"result":"127.0.0.1:4000 whoami"}"#;
+
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+        assert!(!context.contains::<Method>());
+    }
+
+    #[test]
+    fn server_deadline() {
+        let mut server = Server::new();
+        server
+            .register("remaining", |context: &Context, _params| {
+                let remaining = context.remaining().ok_or_else(Error::new_internal_error)?;
+                Ok(Value::Bool(remaining > Duration::from_secs(30)))
+            })
+            .unwrap();
+        let input = r#"{"id":1,"method":"remaining","params":[],"deadline":60000}"#;
+
+        let expected = r#"{"error":{"code":-32600,"message":"Invalid request"},"id":1}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        server.set_strict(false);
+        let expected = r#"{"id":1,"result":true}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let input = r#"{"id":1,"method":"remaining","params":[],"deadline":0}"#;
+        let expected = r#"{"error":{"code":-32005,"message":"Deadline exceeded"},"id":1}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        // The farthest deadline does not overflow.
+        let input = r#"{"id":1,"method":"remaining","params":[],"deadline":18446744073709551615}"#;
+        let expected = r#"{"id":1,"result":true}"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn server_cancel() {
+        let (started, wait_started) = std::sync::mpsc::channel();
+        let started = std::sync::Mutex::new(started);
+
+        let mut server = Server::new();
+        server
+            .register("wait", move |context: &Context, _params| {
+                let token = context
+                    .get::<CancellationToken>()
+                    .ok_or_else(Error::new_internal_error)?;
+                started.lock().unwrap().send(()).unwrap();
+                while !token.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Ok(Value::Null)
+            })
+            .unwrap();
+        let server = Arc::new(server);
+
+        let mut context = Context::new();
+        context.insert(Connection(1));
+        let call = {
+            let server = server.clone();
+            let context = context.clone();
+            std::thread::spawn(move || {
+                let input = r#"{"id":1,"method":"wait","params":[]}"#;
+                server.handle_with(&context, serde_json::from_str(input).unwrap())
+            })
+        };
+        wait_started.recv().unwrap();
+
+        let input = r#"{"method":"$/cancel","params":{"id":1}}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(result, None);
+
+        let expected = r#"{"error":{"code":-32006,"message":"Cancelled"},"id":1}"#;
+        let result = call.join().unwrap();
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn server_progress() {
+        let mut server = Server::new();
+        server
+            .register("count", |context: &Context, _params| {
+                let progress = context
+                    .get::<Progress>()
+                    .ok_or_else(Error::new_internal_error)?;
+                progress.report(Value::from(1));
+                progress.report(Value::from(2));
+                Ok(Value::from(2))
+            })
+            .unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut context = Context::new();
+        context.insert(Outbox::new(move |notification| {
+            sender.send(notification).is_ok()
+        }));
+
+        let input = r#"{"id":7,"method":"count","params":[]}"#;
+        let expected = r#"{"id":7,"result":2}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let expected = vec![
+            r#"{"method":"$/progress","params":{"id":7,"value":1}}"#,
+            r#"{"method":"$/progress","params":{"id":7,"value":2}}"#,
+        ];
+        let result: Vec<_> = receiver
+            .try_iter()
+            .map(|notification| serde_json::to_string(&notification).unwrap())
+            .collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn server_serve() {
+        struct Echo;
+
+        impl Service for Echo {
+            fn methods(&self) -> &'static [&'static str] {
+                &["echo", "echo.first"]
+            }
+
+            fn call(
+                &self,
+                _context: &Context,
+                method: &str,
+                params: Params,
+            ) -> Result<Value, Error> {
+                match (method, params) {
+                    ("echo", params) => Ok(json!(params)),
+                    ("echo.first", Params::Array(values)) => Ok(json!(values.first())),
+                    _ => Err(Error::new_invalid_params()),
+                }
+            }
+        }
+
+        let mut server = Server::new();
+        server.serve(Echo).unwrap();
+
+        let input = r#"[
+            {"id":1,"method":"echo","params":[1,2]},
+            {"id":2,"method":"echo.first","params":[1,2]}
+        ]"#;
+        let expected = r#"[{"id":1,"result":[1,2]},{"id":2,"result":1}]"#;
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        // Methods are not replaced.
+        let result = server.register("echo.first", |_: &Context, _params| Ok(Value::Null));
+        assert_eq!(result, Err(Collision("echo.first".to_owned())));
+        assert_eq!(server.serve(Echo), Err(Collision("echo".to_owned())));
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn server_metrics() {
+        let metrics = Arc::new(InMemory::new());
+        let mut server = server();
+        server.set_metrics(metrics.clone());
+
+        let input = r#"[{"method":"sum","params":[1]},{"id":1,"method":"sum","params":{}},{"id":2,"method":"foo","params":[]},{"method":"bar","params":[]}]"#;
+        server.handle(serde_json::from_str(input).unwrap());
+
+        assert_eq!(metrics.requests(Some("sum")), 2);
+        assert_eq!(metrics.errors(Some("sum"), &Code::InvalidParams), 1);
+
+        // Unknown methods are recorded together, without name.
+        assert_eq!(metrics.requests(Some("foo")), 0);
+        assert_eq!(metrics.requests(None), 2);
+        assert_eq!(metrics.errors(None, &Code::MethodNotFound), 2);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn server_tracing() {
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id as SpanId, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        type Spans = Mutex<Vec<(String, Vec<String>)>>;
+
+        struct Recorder(Arc<Spans>);
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _metadata: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes) -> SpanId {
+                let mut spans = self.0.lock().unwrap();
+                spans.push((span.metadata().name().to_owned(), Vec::new()));
+                let id = SpanId::from_u64(spans.len() as u64);
+                drop(spans);
+                span.record(&mut Visitor(&self.0, id.into_u64()));
+                id
+            }
+
+            fn record(&self, span: &SpanId, values: &Record) {
+                values.record(&mut Visitor(&self.0, span.into_u64()));
+            }
+
+            fn record_follows_from(&self, _span: &SpanId, _follows: &SpanId) {}
+
+            fn event(&self, _event: &Event) {}
+
+            fn enter(&self, _span: &SpanId) {}
+
+            fn exit(&self, _span: &SpanId) {}
+        }
+
+        struct Visitor<'a>(&'a Spans, u64);
+
+        impl Visit for Visitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let mut spans = self.0.lock().unwrap();
+                let fields = &mut spans[self.1 as usize - 1].1;
+                fields.push(format!("{}={:?}", field.name(), value));
+            }
+        }
+
+        let spans = Arc::new(Spans::default());
+        let input = r#"[{"method":"sum","params":[1]},{"id":1,"method":"sum","params":{}}]"#;
+
+        tracing::subscriber::with_default(Recorder(spans.clone()), || {
+            server().handle(serde_json::from_str(input).unwrap());
+        });
+
+        let expected = vec![
+            ("rpc.batch".to_owned(), vec!["size=2".to_owned()]),
+            ("rpc.notification".to_owned(), vec!["method=sum".to_owned()]),
+            (
+                "rpc.call".to_owned(),
+                vec![
+                    "method=sum".to_owned(),
+                    "id=1".to_owned(),
+                    "code=-32602".to_owned(),
+                    "message=\"Invalid params\"".to_owned(),
+                ],
+            ),
+        ];
+        assert_eq!(*spans.lock().unwrap(), expected);
+    }
+}
@@ -0,0 +1,107 @@
+//! MINI-RPC Services.
+//!
+//! A [`Service`] dispatches several methods by name, and is served with
+//! `Server::serve`. Services are usually generated from a trait by the `service`
+//! attribute of the `minirpc-derive` crate (re-exported with the `derive` feature),
+//! whose generated code decodes params with [`Args`] and encodes results with
+//! [`to_value`].
+
+use crate::{Context, Error, Params};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Dispatcher of several methods.
+pub trait Service: Send + Sync {
+    /// Returns the names of the methods of the service.
+    fn methods(&self) -> &'static [&'static str];
+
+    /// Invokes `method` with given `params`.
+    fn call(&self, context: &Context, method: &str, params: Params) -> Result<Value, Error>;
+}
+
+/// Decoder of the arguments of a method, given by position or by name.
+#[derive(Debug)]
+pub struct Args {
+    params: Params,
+    index: usize,
+}
+
+impl Args {
+    /// Creates a new `Args` decoding given `params`.
+    pub fn new(params: Params) -> Self {
+        Self { params, index: 0 }
+    }
+
+    /// Decodes the next argument, named `name`.
+    ///
+    /// A missing argument decodes as `null`, so that optional arguments can be omitted.
+    pub fn next<T>(&mut self, name: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let value = match &mut self.params {
+            Params::Array(values) => values.get_mut(self.index).map(Value::take),
+            Params::Object(map) => map.remove(name),
+        };
+        self.index += 1;
+        serde_json::from_value(value.unwrap_or(Value::Null))
+            .map_err(|_| Error::new_invalid_params())
+    }
+
+    /// Checks that no argument is left over.
+    pub fn finish(self) -> Result<(), Error> {
+        let left = match &self.params {
+            Params::Array(values) => values.len() > self.index,
+            Params::Object(map) => !map.is_empty(),
+        };
+        if left {
+            return Err(Error::new_invalid_params());
+        }
+        Ok(())
+    }
+}
+
+/// Encodes given `value`, as an argument or a result.
+pub fn to_value<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json::to_value(value).map_err(|_| Error::new_internal_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_next() {
+        let input = serde_json::from_str(r#"[1,"a"]"#).unwrap();
+        let mut args = Args::new(input);
+
+        assert_eq!(args.next::<u64>("a"), Ok(1));
+        assert_eq!(args.next::<String>("b"), Ok("a".to_owned()));
+        assert_eq!(args.next::<Option<u64>>("c"), Ok(None));
+        assert_eq!(args.finish(), Ok(()));
+
+        let input = serde_json::from_str(r#"{"b":"a","a":1}"#).unwrap();
+        let mut args = Args::new(input);
+
+        assert_eq!(args.next::<u64>("a"), Ok(1));
+        assert_eq!(args.next::<u64>("b"), Err(Error::new_invalid_params()));
+        assert_eq!(args.finish(), Ok(()));
+    }
+
+    #[test]
+    fn args_finish() {
+        let input = serde_json::from_str(r#"[1,2]"#).unwrap();
+        let mut args = Args::new(input);
+        args.next::<u64>("a").unwrap();
+        assert_eq!(args.finish(), Err(Error::new_invalid_params()));
+
+        let input = serde_json::from_str(r#"{"a":1,"b":2}"#).unwrap();
+        let mut args = Args::new(input);
+        args.next::<u64>("a").unwrap();
+        assert_eq!(args.finish(), Err(Error::new_invalid_params()));
+    }
+}
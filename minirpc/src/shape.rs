@@ -0,0 +1,111 @@
+//! MINI-RPC Protocol Params.
+//!
+//! The params of the protocol messages (cancellations, progress reports, chunks and
+//! subscription events and ends) all hold the id of a call or subscription, along with a
+//! value for most of them. Their [`Shape`]s are defined here, once for both the `Client`
+//! building them and the `Server` parsing them, or the other way around.
+
+use crate::{Error, Id, Params};
+use serde_json::{Map, Value};
+
+/// Shape of the params of a protocol message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// Object holding the id under the first key, and the value (if any) under the
+    /// second one.
+    Object(&'static str, Option<&'static str>),
+
+    /// Array holding the id only.
+    Array,
+}
+
+/// Params of the `$/cancel` notification.
+pub const CANCEL: Shape = Shape::Object("id", None);
+
+/// Params of the `$/progress` notification.
+pub const PROGRESS: Shape = Shape::Object("id", Some("value"));
+
+/// Params of the `$/chunk` notification.
+pub const CHUNK: Shape = Shape::Object("id", Some("item"));
+
+/// Params of the `$/subscription` notification.
+pub const SUBSCRIPTION: Shape = Shape::Object("subscription", Some("result"));
+
+/// Params of the call ending a subscription.
+pub const UNSUBSCRIBE: Shape = Shape::Array;
+
+impl Shape {
+    /// Returns the params holding `id` and `value`, which is dropped if the shape has no
+    /// value.
+    pub fn params(&self, id: &Id, value: Value) -> Params {
+        match *self {
+            Shape::Object(id_key, value_key) => {
+                let mut map = Map::new();
+                map.insert(id_key.to_owned(), json!(id));
+                if let Some(value_key) = value_key {
+                    map.insert(value_key.to_owned(), value);
+                }
+                Params::Object(map)
+            }
+            Shape::Array => Params::Array(vec![json!(id)]),
+        }
+    }
+
+    /// Returns the id and the value (`null` if missing) held by given `params`.
+    pub fn parse(&self, params: Params) -> Result<(Id, Value), Error> {
+        let (id, value) = match (*self, params) {
+            (Shape::Object(id_key, value_key), Params::Object(mut map)) => {
+                let value = value_key.and_then(|value_key| map.remove(value_key));
+                (map.remove(id_key), value)
+            }
+            (Shape::Array, Params::Array(mut values)) if values.len() == 1 => (values.pop(), None),
+            _ => (None, None),
+        };
+        let id = id
+            .and_then(|id| serde_json::from_value(id).ok())
+            .ok_or_else(Error::new_invalid_params)?;
+        Ok((id, value.unwrap_or(Value::Null)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shape_object() {
+        let input = (Id::Number(1), json!({"done": 1, "total": 2}));
+        let expected = r#"{"id":1,"value":{"done":1,"total":2}}"#;
+
+        let result = PROGRESS.params(&input.0, input.1.clone());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+        assert_eq!(PROGRESS.parse(result), Ok(input));
+
+        let result = CANCEL.params(&Id::Number(1), Value::from("ignored"));
+        assert_eq!(serde_json::to_string(&result).unwrap(), r#"{"id":1}"#);
+        assert_eq!(CANCEL.parse(result), Ok((Id::Number(1), Value::Null)));
+
+        let expected = r#"{"result":"a","subscription":2}"#;
+        let result = SUBSCRIPTION.params(&Id::Number(2), Value::from("a"));
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn shape_array() {
+        let result = UNSUBSCRIBE.params(&Id::Number(1), Value::Null);
+        assert_eq!(serde_json::to_string(&result).unwrap(), "[1]");
+        assert_eq!(UNSUBSCRIBE.parse(result), Ok((Id::Number(1), Value::Null)));
+    }
+
+    #[test]
+    fn shape_invalid() {
+        let invalid = Err(Error::new_invalid_params());
+        assert_eq!(CANCEL.parse(Params::Array(vec![1.into()])), invalid);
+        assert_eq!(CHUNK.parse(Params::Object(Map::new())), invalid);
+        assert_eq!(UNSUBSCRIBE.parse(Params::Array(vec![])), invalid);
+        assert_eq!(
+            UNSUBSCRIBE.parse(Params::Array(vec![1.into(), 2.into()])),
+            invalid
+        );
+    }
+}
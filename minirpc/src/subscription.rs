@@ -0,0 +1,220 @@
+//! MINI-RPC Subscriptions.
+//!
+//! A subscribe method handler registers a subscription with [`Subscriptions::subscribe`],
+//! answers the call with its id, and hands the returned [`Subscriber`] to the source of
+//! events. Each event is sent to the peer as a [`SUBSCRIPTION`] notification whose
+//! params hold the `subscription` id and the `result`, through the `Outbox` of the
+//! connection.
+//!
+//! Subscriptions are tracked per `Connection`: they end when the peer unsubscribes,
+//! through the manager registered as the handler of an unsubscribe method, or when the
+//! transport reports the connection closed with [`Subscriptions::disconnect`].
+
+use crate::context::{Connection, Outbox};
+use crate::server::Handler;
+use crate::shape;
+use crate::{Context, Error, Id, Method, Notification, Params};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Method of the notification carrying a subscription event.
+pub const SUBSCRIPTION: &str = "$/subscription";
+
+/// Subscription manager.
+///
+/// Clones share the same subscriptions. As a `Handler`, it unsubscribes the subscription
+/// whose id is given as single param, answering whether it was active.
+#[derive(Clone, Debug, Default)]
+pub struct Subscriptions {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    active: HashMap<Id, Connection>,
+}
+
+impl Subscriptions {
+    /// Creates a new `Subscriptions` manager without any subscription.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a subscription of the peer of given `context`, which must hold the
+    /// `Connection` and `Outbox` of the transport.
+    pub fn subscribe(&self, context: &Context) -> Result<Subscriber, Error> {
+        let connection = *context
+            .get::<Connection>()
+            .ok_or_else(Error::new_internal_error)?;
+        let outbox = context
+            .get::<Outbox>()
+            .cloned()
+            .ok_or_else(Error::new_internal_error)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let id = Id::Number(state.next_id);
+        state.active.insert(id.clone(), connection);
+
+        Ok(Subscriber {
+            id,
+            connection,
+            outbox,
+            subscriptions: self.clone(),
+        })
+    }
+
+    /// Ends subscription `id` of the peer of given `context`, returning `true` if it was
+    /// active.
+    pub fn unsubscribe(&self, context: &Context, id: &Id) -> bool {
+        let connection = context.get::<Connection>();
+        let mut state = self.state.lock().unwrap();
+        if state.active.get(id) != connection {
+            return false;
+        }
+        state.active.remove(id).is_some()
+    }
+
+    /// Ends all the subscriptions of `connection`, returning how many were active.
+    pub fn disconnect(&self, connection: Connection) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let len = state.active.len();
+        state.active.retain(|_, active| *active != connection);
+        len - state.active.len()
+    }
+
+    /// Returns the number of active subscriptions.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().active.len()
+    }
+
+    /// Returns `true` if there is no active subscription.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_active(&self, id: &Id, connection: Connection) -> bool {
+        self.state.lock().unwrap().active.get(id) == Some(&connection)
+    }
+}
+
+impl Handler for Subscriptions {
+    fn handle(&self, context: &Context, params: Params) -> Result<Value, Error> {
+        let (id, _) = shape::UNSUBSCRIBE.parse(params)?;
+        Ok(Value::Bool(self.unsubscribe(context, &id)))
+    }
+}
+
+/// Sender of the events of a subscription.
+#[derive(Clone, Debug)]
+pub struct Subscriber {
+    id: Id,
+    connection: Connection,
+    outbox: Outbox,
+    subscriptions: Subscriptions,
+}
+
+impl Subscriber {
+    /// Returns the subscription id.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Sends given event `result` to the peer, returning `false` once the subscription
+    /// ended or the peer is gone, for the source of events to stop.
+    pub fn send(&self, result: Value) -> bool {
+        if !self.subscriptions.is_active(&self.id, self.connection) {
+            return false;
+        }
+        self.outbox.send(Notification {
+            method: Method::String(SUBSCRIPTION.to_owned()),
+            params: shape::SUBSCRIPTION.params(&self.id, result),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Server;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn connect(connection: u64) -> (Context, Receiver<Notification>) {
+        let (sender, receiver) = mpsc::channel();
+        let mut context = Context::new();
+        context.insert(Connection(connection));
+        context.insert(Outbox::new(move |notification| {
+            sender.send(notification).is_ok()
+        }));
+        (context, receiver)
+    }
+
+    #[test]
+    fn subscriptions_send() {
+        let subscriptions = Subscriptions::new();
+        let (context, receiver) = connect(1);
+        let subscriber = subscriptions.subscribe(&context).unwrap();
+
+        assert!(subscriber.send(Value::from(1)));
+        let expected = r#"{"method":"$/subscription","params":{"result":1,"subscription":1}}"#;
+        let result = receiver.try_recv().unwrap();
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        // Only the connection which subscribed can unsubscribe.
+        let (other, _) = connect(2);
+        assert!(!subscriptions.unsubscribe(&other, subscriber.id()));
+        assert!(subscriptions.unsubscribe(&context, subscriber.id()));
+        assert!(!subscriber.send(Value::from(2)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn subscriptions_disconnect() {
+        let subscriptions = Subscriptions::new();
+        let (first, _) = connect(1);
+        let (second, _receiver) = connect(2);
+        subscriptions.subscribe(&first).unwrap();
+        subscriptions.subscribe(&first).unwrap();
+        let subscriber = subscriptions.subscribe(&second).unwrap();
+
+        assert_eq!(subscriptions.disconnect(Connection(1)), 2);
+        assert_eq!(subscriptions.len(), 1);
+        assert!(subscriber.send(Value::Null));
+        assert_eq!(
+            subscriptions.subscribe(&Context::new()).unwrap_err(),
+            Error::new_internal_error()
+        );
+    }
+
+    #[test]
+    fn subscriptions_handle() {
+        let subscriptions = Subscriptions::new();
+        let mut server = Server::new();
+        server
+            .register("subscribe", {
+                let subscriptions = subscriptions.clone();
+                move |context: &Context, _params| {
+                    let subscriber = subscriptions.subscribe(context)?;
+                    Ok(json!(subscriber.id()))
+                }
+            })
+            .unwrap();
+        server
+            .register("unsubscribe", subscriptions.clone())
+            .unwrap();
+        let (context, _) = connect(1);
+
+        let input = r#"{"id":1,"method":"subscribe","params":[]}"#;
+        let expected = r#"{"id":1,"result":1}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+
+        let input = r#"{"id":2,"method":"unsubscribe","params":[1]}"#;
+        let expected = r#"{"id":2,"result":true}"#;
+        let result = server.handle_with(&context, serde_json::from_str(input).unwrap());
+        assert_eq!(serde_json::to_string(&result).unwrap(), expected);
+        assert!(subscriptions.is_empty());
+    }
+}
@@ -4,13 +4,13 @@ use crate::Id;
 use serde_json::Value;
 
 /// Response success.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub struct Success {
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Success<R = Value> {
     /// Correlation id.
     pub id: Id,
 
     /// Result.
-    pub result: Value,
+    pub result: R,
 }
 
 #[cfg(test)]
@@ -41,4 +41,19 @@ mod tests {
         let result = serde_json::to_string(&input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[cfg(feature = "raw_value")]
+    #[test]
+    fn success_raw_value_passthrough() {
+        use serde_json::value::RawValue;
+
+        let input = r#"{"id":1,"result":{"b": 2, "a":[1,  2]}}"#;
+
+        let result: Success<Box<RawValue>> = serde_json::from_str(input).unwrap();
+        assert_eq!(result.id, Id::Number(1));
+        assert_eq!(result.result.get(), r#"{"b": 2, "a":[1,  2]}"#);
+
+        let result = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, input);
+    }
 }
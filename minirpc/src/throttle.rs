@@ -0,0 +1,425 @@
+//! MINI-RPC Rate and Concurrency Limits.
+//!
+//! The [`Throttle`] interceptor enforces [`Quota`]s globally, per `Peer` (as inserted in
+//! the `Context` by the transport, keyed on its IP address) and per method. Rates are
+//! enforced with token buckets, concurrency by counting requests in flight: a guard is
+//! stored in the per-request `Context`, holding the slots of every throttle which
+//! admitted the request, from which the `Server` takes it to release them once the
+//! request is handled.
+//!
+//! Excess requests are rejected with `Error::new_rate_limited()`, whose data names the
+//! exhausted `scope` and, for rates, the `retry_after_ms` before a new request is let
+//! through.
+
+use crate::context::Peer;
+use crate::interceptor::Interceptor;
+use crate::{Call, Context, Error, Method, Notification};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limits of a scope.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Quota {
+    rate: Option<(u32, Duration)>,
+    max_in_flight: Option<usize>,
+}
+
+impl Quota {
+    /// Creates a new `Quota` without any limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows up to `requests` requests per `period`, in bursts of up to `requests`.
+    pub fn with_rate(mut self, requests: u32, period: Duration) -> Self {
+        self.rate = Some((requests, period));
+        self
+    }
+
+    /// Allows up to `max` requests being handled at once.
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+        self
+    }
+}
+
+/// Interceptor enforcing quotas.
+#[derive(Debug, Default)]
+pub struct Throttle {
+    global: Option<Quota>,
+    peer: Option<Quota>,
+    methods: HashMap<String, Quota>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Scope {
+    Global,
+    Peer(String),
+    Method(String),
+}
+
+impl Scope {
+    fn name(&self) -> &'static str {
+        match self {
+            Scope::Global => "global",
+            Scope::Peer(_) => "peer",
+            Scope::Method(_) => "method",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    buckets: HashMap<Scope, Bucket>,
+    in_flight: HashMap<Scope, usize>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Maximum number of buckets: the full ones are forgotten first, then the least recently
+/// used ones.
+const MAX_BUCKETS: usize = 4096;
+
+impl Throttle {
+    /// Creates a new `Throttle` without any quota.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the quota shared by all requests.
+    pub fn set_global(&mut self, quota: Quota) {
+        self.global = Some(quota);
+    }
+
+    /// Sets the quota applied to the requests of each peer.
+    pub fn set_peer(&mut self, quota: Quota) {
+        self.peer = Some(quota);
+    }
+
+    /// Sets the quota applied to the requests of `method`.
+    pub fn set_method(&mut self, method: &str, quota: Quota) {
+        self.methods.insert(method.to_owned(), quota);
+    }
+
+    /// Admits a request of `method` from `peer`, returning the guard holding its
+    /// in-flight slots.
+    ///
+    /// A request is admitted only if every applicable quota allows it, in which case it
+    /// is counted against all of them.
+    pub fn acquire(&self, peer: Option<&Peer>, method: &Method) -> Result<InFlight, Error> {
+        let mut scopes = Vec::with_capacity(3);
+        if let Some(quota) = &self.global {
+            scopes.push((Scope::Global, quota));
+        }
+        if let (Some(quota), Some(peer)) = (&self.peer, peer) {
+            scopes.push((Scope::Peer(host(peer)), quota));
+        }
+        if let Some(quota) = self.methods.get(method.as_str()) {
+            scopes.push((Scope::Method(method.as_str().to_owned()), quota));
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.buckets.len() >= MAX_BUCKETS {
+            state.prune(|scope| self.rate(scope), now);
+        }
+
+        for (scope, quota) in &scopes {
+            if let Some(max) = quota.max_in_flight {
+                if state.in_flight.get(scope).copied().unwrap_or(0) >= max {
+                    return Err(Error::new_rate_limited().with_data(json!({
+                        "scope": scope.name(),
+                        "max_in_flight": max,
+                    })));
+                }
+            }
+            if let Some(rate) = quota.rate {
+                let bucket = state.bucket(scope, rate, now);
+                if bucket.tokens < 1.0 {
+                    let retry_after = (1.0 - bucket.tokens) / per_second(rate);
+                    return Err(Error::new_rate_limited().with_data(json!({
+                        "scope": scope.name(),
+                        "retry_after_ms": (retry_after * 1000.0).ceil() as u64,
+                    })));
+                }
+            }
+        }
+
+        let mut held = Vec::new();
+        for (scope, quota) in scopes {
+            if quota.rate.is_some() {
+                if let Some(bucket) = state.buckets.get_mut(&scope) {
+                    bucket.tokens -= 1.0;
+                }
+            }
+            if quota.max_in_flight.is_some() {
+                *state.in_flight.entry(scope.clone()).or_insert(0) += 1;
+                held.push(scope);
+            }
+        }
+
+        Ok(InFlight {
+            state: Arc::clone(&self.state),
+            scopes: held,
+            previous: None,
+        })
+    }
+
+    /// Returns the rate limit of `scope`.
+    fn rate(&self, scope: &Scope) -> Option<(u32, Duration)> {
+        match scope {
+            Scope::Global => self.global.and_then(|quota| quota.rate),
+            Scope::Peer(_) => self.peer.and_then(|quota| quota.rate),
+            Scope::Method(method) => self.methods.get(method).and_then(|quota| quota.rate),
+        }
+    }
+
+    fn admit(&self, context: &mut Context, method: &Method) -> Result<(), Error> {
+        let mut in_flight = self.acquire(context.get(), method)?;
+        // Keep the slots held for the request by the throttles admitting it before.
+        in_flight.previous = context.take::<InFlight>();
+        context.insert(in_flight);
+        Ok(())
+    }
+}
+
+impl State {
+    /// Returns the bucket of `scope`, refilled up to `now`.
+    fn bucket(&mut self, scope: &Scope, rate: (u32, Duration), now: Instant) -> &mut Bucket {
+        let bucket = self.buckets.entry(scope.clone()).or_insert(Bucket {
+            tokens: f64::from(rate.0),
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second(rate)).min(f64::from(rate.0));
+        bucket.updated = now;
+        bucket
+    }
+
+    /// Forgets the buckets which refilled, being equivalent to new ones, then if there
+    /// are still too many, the least recently used ones down to half the maximum.
+    fn prune<F>(&mut self, rate: F, now: Instant)
+    where
+        F: Fn(&Scope) -> Option<(u32, Duration)>,
+    {
+        self.buckets.retain(|scope, bucket| match rate(scope) {
+            Some(rate) => {
+                let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * per_second(rate) < f64::from(rate.0)
+            }
+            None => false,
+        });
+
+        if self.buckets.len() >= MAX_BUCKETS {
+            let mut updated: Vec<_> = self.buckets.values().map(|bucket| bucket.updated).collect();
+            let (_, &mut oldest, _) = updated.select_nth_unstable(MAX_BUCKETS / 2);
+            self.buckets.retain(|_, bucket| bucket.updated > oldest);
+        }
+    }
+}
+
+/// Returns the IP address of `peer`, or its whole address if it has no port.
+fn host(peer: &Peer) -> String {
+    match peer.0.parse::<SocketAddr>() {
+        Ok(address) => address.ip().to_string(),
+        Err(_) => peer.0.clone(),
+    }
+}
+
+fn per_second((requests, period): (u32, Duration)) -> f64 {
+    f64::from(requests) / period.as_secs_f64()
+}
+
+impl Interceptor for Throttle {
+    fn before_call(&self, context: &mut Context, call: &mut Call) -> Result<(), Error> {
+        self.admit(context, &call.method)
+    }
+
+    fn before_notification(
+        &self,
+        context: &mut Context,
+        notification: &mut Notification,
+    ) -> Result<(), Error> {
+        self.admit(context, &notification.method)
+    }
+}
+
+/// In-flight slots of an admitted request, released on drop.
+#[derive(Debug)]
+pub struct InFlight {
+    state: Arc<Mutex<State>>,
+    scopes: Vec<Scope>,
+    previous: Option<Arc<InFlight>>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        for scope in self.scopes.drain(..) {
+            if let Some(count) = state.in_flight.get_mut(&scope) {
+                *count -= 1;
+                if *count == 0 {
+                    state.in_flight.remove(&scope);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Params, Server};
+    use serde_json::Value;
+
+    #[test]
+    fn throttle_rate() {
+        let mut throttle = Throttle::new();
+        throttle.set_peer(Quota::new().with_rate(2, Duration::from_secs(60)));
+        let alice = Peer("10.0.0.1:4000".to_owned());
+        let bob = Peer("10.0.0.2:4000".to_owned());
+        let method = Method::String("sum".to_owned());
+
+        assert!(throttle.acquire(Some(&alice), &method).is_ok());
+        assert!(throttle.acquire(Some(&alice), &method).is_ok());
+        assert!(throttle.acquire(Some(&bob), &method).is_ok());
+
+        // Peers are keyed on their IP address, whatever the port.
+        let alice = Peer("10.0.0.1:4001".to_owned());
+        let error = throttle.acquire(Some(&alice), &method).unwrap_err();
+        assert_eq!(error.message, "Rate limited");
+        let data = error.data.unwrap();
+        assert_eq!(data["scope"], "peer");
+        assert!(data["retry_after_ms"].as_u64().unwrap() > 29_000);
+    }
+
+    #[test]
+    fn throttle_in_flight() {
+        let mut throttle = Throttle::new();
+        throttle.set_global(Quota::new().with_max_in_flight(2));
+        throttle.set_method("slow", Quota::new().with_max_in_flight(1));
+        let slow = Method::String("slow".to_owned());
+        let fast = Method::String("fast".to_owned());
+
+        let first = throttle.acquire(None, &slow).unwrap();
+        let error = throttle.acquire(None, &slow).unwrap_err();
+        assert_eq!(
+            error.data.unwrap(),
+            json!({"scope":"method","max_in_flight":1})
+        );
+
+        // Rejected requests do not hold any slot.
+        let second = throttle.acquire(None, &fast).unwrap();
+        let error = throttle.acquire(None, &fast).unwrap_err();
+        assert_eq!(
+            error.data.unwrap(),
+            json!({"scope":"global","max_in_flight":2})
+        );
+
+        drop(first);
+        drop(second);
+        assert!(throttle.acquire(None, &slow).is_ok());
+        assert!(throttle.state.lock().unwrap().in_flight.is_empty());
+    }
+
+    #[test]
+    fn throttle_prune() {
+        let rate = (1, Duration::from_secs(60));
+        let start = Instant::now();
+        let mut state = State::default();
+        for index in 0..MAX_BUCKETS {
+            let scope = Scope::Peer(index.to_string());
+            state.bucket(&scope, rate, start + Duration::from_millis(index as u64));
+            state.buckets.get_mut(&scope).unwrap().tokens = 0.0;
+        }
+
+        // No bucket refilled: the least recently used ones are evicted.
+        state.prune(|_| Some(rate), start + Duration::from_secs(1));
+        assert!(state.buckets.len() < MAX_BUCKETS / 2);
+        let newest = Scope::Peer((MAX_BUCKETS - 1).to_string());
+        assert!(state.buckets.contains_key(&newest));
+        assert!(!state.buckets.contains_key(&Scope::Peer("0".to_owned())));
+    }
+
+    #[test]
+    fn throttle_intercept() {
+        let mut throttle = Throttle::new();
+        throttle.set_method("sum", Quota::new().with_rate(1, Duration::from_secs(60)));
+
+        let mut server = Server::new();
+        server.intercept(throttle);
+        server
+            .register("sum", |_: &Context, _: Params| Ok(Value::from(0)))
+            .unwrap();
+        let input = r#"[
+            {"id":1,"method":"sum","params":[]},
+            {"id":2,"method":"sum","params":[]}
+        ]"#;
+
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        let result = serde_json::to_value(&result).unwrap();
+        assert_eq!(result[0], json!({"id":1,"result":0}));
+        assert_eq!(result[1]["error"]["code"], -32003);
+        assert_eq!(result[1]["error"]["data"]["scope"], "method");
+    }
+
+    #[test]
+    fn throttle_chain() {
+        let mut global = Throttle::new();
+        global.set_global(Quota::new().with_max_in_flight(1));
+        let state = Arc::clone(&global.state);
+        let mut method = Throttle::new();
+        method.set_method("sum", Quota::new().with_max_in_flight(1));
+
+        // The slots of both throttles are held until the call is answered.
+        let mut server = Server::new();
+        server.intercept(global);
+        server.intercept(method);
+        server
+            .register("sum", move |_: &Context, _: Params| {
+                Ok(Value::from(state.lock().unwrap().in_flight.len()))
+            })
+            .unwrap();
+        let input = r#"{"id":1,"method":"sum","params":[]}"#;
+
+        for _ in 0..2 {
+            let result = server.handle(serde_json::from_str(input).unwrap());
+            let expected = json!({"id":1,"result":1});
+            assert_eq!(serde_json::to_value(&result).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn throttle_release() {
+        let mut throttle = Throttle::new();
+        throttle.set_global(Quota::new().with_max_in_flight(1));
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+
+        // Slots are released once answered, even if a handler keeps the context.
+        let mut server = Server::new();
+        server.intercept(throttle);
+        let kept = Arc::clone(&contexts);
+        server
+            .register("keep", move |context: &Context, _: Params| {
+                kept.lock().unwrap().push(context.clone());
+                Ok(Value::from(0))
+            })
+            .unwrap();
+        let input = r#"[
+            {"id":1,"method":"keep","params":[]},
+            {"id":2,"method":"keep","params":[]}
+        ]"#;
+
+        let result = server.handle(serde_json::from_str(input).unwrap());
+        let expected = json!([{"id":1,"result":0},{"id":2,"result":0}]);
+        assert_eq!(serde_json::to_value(&result).unwrap(), expected);
+        assert_eq!(contexts.lock().unwrap().len(), 2);
+    }
+}